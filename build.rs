@@ -0,0 +1,33 @@
+use std::process::Command;
+
+fn main() {
+    // Record the exact git commit (and whether the tree is dirty) that we
+    // build from, so that we can trace every result back to the code
+    let git_sha = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let is_dirty = Command::new("git")
+        .args(["status", "--porcelain", "--untracked-files=no"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| !output.stdout.is_empty())
+        .unwrap_or(false);
+
+    let git_version = if is_dirty {
+        format!("{git_sha}-dirty")
+    } else {
+        git_sha
+    };
+    println!("cargo:rustc-env=SC2_EXP_GIT_VERSION={git_version}");
+
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/index");
+    println!("cargo:rerun-if-changed=.git/refs");
+    println!("cargo:rerun-if-changed=src");
+}
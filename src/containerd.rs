@@ -1,55 +1,635 @@
-use crate::env::Env;
+use crate::{env::Env, kubernetes::K8s};
 use chrono::{DateTime, Utc};
 use log::{debug, warn};
 use plotters::prelude::RGBColor;
 use regex::Regex;
 use serde_json::Value;
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Stdio};
 use std::{
-    collections::BTreeMap,
-    io::{BufRead, BufReader},
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt, fs,
+    io::{self, BufRead, BufReader},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread, time,
 };
 
+/// Events that we measure the start-up time break-down with. Most of them we
+/// parse from containerd's logs, but some we parse from the snapshotter's
+/// logs, and some we derive ourselves
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum ContainerdEvent {
+    // Fake event that we add to measure end-to-end time
+    StartUp,
+    // This event captures the time to start the sandbox
+    RunPodSandbox,
+    // This event captures the time to pull an image in the host
+    PullImage,
+    CreateContainerUserContainer,
+    CreateContainerQueueProxy,
+    // Total time to create any other (sidecar) containers in the pod
+    CreateContainerSidecars,
+    // For CoCo: pull app image in the guest
+    StartContainerUserContainer,
+    // For the confidential baselines: time to remotely attest the sandbox
+    // (i.e. the attestation agent's handshake with the KBS). It happens
+    // lazily, when pulling the app image in the guest, so it overlaps in
+    // time with StartContainerUserContainer
+    Attestation,
+    // For CoCo: pull sidecar image in the guest
+    StartContainerQueueProxy,
+    // Total time to start any other (sidecar) containers in the pod
+    StartContainerSidecars,
+    // Time to prepare the nydus snapshot (e.g. mount). We parse it from the
+    // nydus-snapshotter's logs, and it overlaps in time with the events
+    // above, so we must not stack it together with them
+    SnapshotPrepare,
+    // Time to lazily fetch the image's layers in the guest. We also parse it
+    // from the nydus-snapshotter's logs, and it overlaps in time with
+    // StartContainerUserContainer
+    GuestLazyPull,
+    // Fake event that we only derive when plotting, as the StartUp time minus
+    // the time spent in all the other (non-overlapping) events
+    Orchestration,
+}
+
+/// Start and end timestamps of each event that we parse from the logs
+pub type EventTimestamps = BTreeMap<ContainerdEvent, (DateTime<Utc>, DateTime<Utc>)>;
+
+/// Ids of the pod's sandbox, and of each of its containers (by name), that we
+/// find in the logs, so that we can go back and inspect a given run
+#[derive(Debug, Default)]
+pub struct PodIds {
+    pub sandbox_id: Option<String>,
+    pub container_ids: BTreeMap<String, String>,
+}
+
+/// Errors that we may hit when parsing the events from the logs, so that
+/// callers can decide whether to retry, or skip, the run
+#[derive(Debug)]
+pub enum ContainerdError {
+    // We failed to spawn, or wait on, journalctl
+    Journalctl(io::Error),
+    // We failed to get journalctl's stdout
+    NoStdout,
+    // We did not find all the events that we expected (only if strict)
+    MissingEvents { expected: usize, got: usize },
+}
+
+impl fmt::Display for ContainerdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerdError::Journalctl(e) => write!(f, "journalctl command failed: {e}"),
+            ContainerdError::NoStdout => write!(f, "failed to open journalctl stdout"),
+            ContainerdError::MissingEvents { expected, got } => {
+                write!(f, "expected {expected} journalctl events but got {got}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ContainerdError {}
+
+impl fmt::Display for ContainerdEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerdEvent::StartUp => write!(f, "StartUp"),
+            ContainerdEvent::RunPodSandbox => write!(f, "RunPodSandbox"),
+            ContainerdEvent::Attestation => write!(f, "Attestation"),
+            ContainerdEvent::PullImage => write!(f, "PullImage"),
+            ContainerdEvent::CreateContainerUserContainer => {
+                write!(f, "CreateContainerUserContainer")
+            }
+            ContainerdEvent::CreateContainerQueueProxy => write!(f, "CreateContainerQueueProxy"),
+            ContainerdEvent::CreateContainerSidecars => write!(f, "CreateContainerSidecars"),
+            ContainerdEvent::StartContainerUserContainer => {
+                write!(f, "StartContainerUserContainer")
+            }
+            ContainerdEvent::StartContainerQueueProxy => write!(f, "StartContainerQueueProxy"),
+            ContainerdEvent::StartContainerSidecars => write!(f, "StartContainerSidecars"),
+            ContainerdEvent::SnapshotPrepare => write!(f, "SnapshotPrepare"),
+            ContainerdEvent::GuestLazyPull => write!(f, "GuestLazyPull"),
+            ContainerdEvent::Orchestration => write!(f, "Orchestration"),
+        }
+    }
+}
+
+impl FromStr for ContainerdEvent {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<ContainerdEvent, Self::Err> {
+        match input {
+            "StartUp" => Ok(ContainerdEvent::StartUp),
+            "RunPodSandbox" => Ok(ContainerdEvent::RunPodSandbox),
+            "Attestation" => Ok(ContainerdEvent::Attestation),
+            "PullImage" => Ok(ContainerdEvent::PullImage),
+            "CreateContainerUserContainer" => Ok(ContainerdEvent::CreateContainerUserContainer),
+            "CreateContainerQueueProxy" => Ok(ContainerdEvent::CreateContainerQueueProxy),
+            "CreateContainerSidecars" => Ok(ContainerdEvent::CreateContainerSidecars),
+            "StartContainerUserContainer" => Ok(ContainerdEvent::StartContainerUserContainer),
+            "StartContainerQueueProxy" => Ok(ContainerdEvent::StartContainerQueueProxy),
+            "StartContainerSidecars" => Ok(ContainerdEvent::StartContainerSidecars),
+            "SnapshotPrepare" => Ok(ContainerdEvent::SnapshotPrepare),
+            "GuestLazyPull" => Ok(ContainerdEvent::GuestLazyPull),
+            "Orchestration" => Ok(ContainerdEvent::Orchestration),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ContainerdEvent {
+    /// Iterate over the events that we parse from containerd's logs (plus
+    /// the end-to-end StartUp), in the order that we stack them in
+    pub fn iter_variants() -> std::slice::Iter<'static, ContainerdEvent> {
+        static VARIANTS: [ContainerdEvent; 10] = [
+            ContainerdEvent::StartUp,
+            ContainerdEvent::RunPodSandbox,
+            ContainerdEvent::PullImage,
+            ContainerdEvent::CreateContainerUserContainer,
+            ContainerdEvent::CreateContainerQueueProxy,
+            ContainerdEvent::CreateContainerSidecars,
+            ContainerdEvent::StartContainerUserContainer,
+            ContainerdEvent::Attestation,
+            ContainerdEvent::StartContainerQueueProxy,
+            ContainerdEvent::StartContainerSidecars,
+        ];
+        VARIANTS.iter()
+    }
+
+    /// Iterate over the events that we parse from the nydus-snapshotter's
+    /// logs
+    pub fn iter_snapshotter_variants() -> std::slice::Iter<'static, ContainerdEvent> {
+        static VARIANTS: [ContainerdEvent; 2] = [
+            ContainerdEvent::SnapshotPrepare,
+            ContainerdEvent::GuestLazyPull,
+        ];
+        VARIANTS.iter()
+    }
+
+    pub fn is_snapshotter_event(&self) -> bool {
+        matches!(
+            self,
+            ContainerdEvent::SnapshotPrepare | ContainerdEvent::GuestLazyPull
+        )
+    }
+
+    /// Events that only some baselines (e.g. the confidential ones attest), or
+    /// some services (e.g. the ones with sidecars), report
+    pub fn is_optional(&self) -> bool {
+        matches!(
+            self,
+            ContainerdEvent::Attestation
+                | ContainerdEvent::CreateContainerSidecars
+                | ContainerdEvent::StartContainerSidecars
+        )
+    }
+
+    /// Events that happen one after the other, so that, added up, they make
+    /// up the end-to-end start-up time (minus the orchestration time).
+    /// Snapshotter events, and events with a parent event, overlap with them
+    pub fn is_sequential(&self) -> bool {
+        *self != ContainerdEvent::StartUp
+            && *self != ContainerdEvent::Orchestration
+            && !self.is_snapshotter_event()
+            && self.parent_event().is_none()
+    }
+
+    /// Some events happen while another (parent) event is in progress. To
+    /// stack them, we carve them out of their parent event
+    pub fn parent_event(&self) -> Option<ContainerdEvent> {
+        match self {
+            ContainerdEvent::Attestation => Some(ContainerdEvent::StartContainerUserContainer),
+            ContainerdEvent::GuestLazyPull => Some(ContainerdEvent::StartContainerUserContainer),
+            _ => None,
+        }
+    }
+
+    /// Human-readable label for the event in the plots' legends. Events that
+    /// we draw with the same colour share the same label
+    pub fn legend_label(&self) -> &'static str {
+        match self {
+            ContainerdEvent::StartUp | ContainerdEvent::Orchestration => "control-plane",
+            ContainerdEvent::RunPodSandbox => "create-vm",
+            ContainerdEvent::Attestation => "attestation",
+            ContainerdEvent::PullImage => "pull-image-host",
+            ContainerdEvent::CreateContainerUserContainer
+            | ContainerdEvent::CreateContainerQueueProxy
+            | ContainerdEvent::CreateContainerSidecars => "create-container",
+            ContainerdEvent::StartContainerUserContainer
+            | ContainerdEvent::StartContainerQueueProxy
+            | ContainerdEvent::StartContainerSidecars => "pull-image-guest",
+            ContainerdEvent::SnapshotPrepare => "snapshot-prepare",
+            ContainerdEvent::GuestLazyPull => "lazy-pull-guest",
+        }
+    }
+
+    pub fn color(&self) -> RGBColor {
+        match self {
+            ContainerdEvent::StartUp => RGBColor(102, 102, 255),
+            ContainerdEvent::RunPodSandbox => RGBColor(102, 255, 178),
+            // Another darker shade of StartContainerUserContainer
+            ContainerdEvent::Attestation => RGBColor(153, 153, 0),
+            ContainerdEvent::PullImage => RGBColor(245, 161, 66),
+            ContainerdEvent::CreateContainerUserContainer => RGBColor(255, 102, 178),
+            ContainerdEvent::CreateContainerQueueProxy => RGBColor(255, 102, 178),
+            ContainerdEvent::CreateContainerSidecars => RGBColor(255, 102, 178),
+            ContainerdEvent::StartContainerUserContainer => RGBColor(255, 255, 102),
+            ContainerdEvent::StartContainerQueueProxy => RGBColor(255, 255, 102),
+            ContainerdEvent::StartContainerSidecars => RGBColor(255, 255, 102),
+            ContainerdEvent::SnapshotPrepare => RGBColor(153, 204, 255),
+            // A darker shade of StartContainerUserContainer, that it is part of
+            ContainerdEvent::GuestLazyPull => RGBColor(204, 204, 0),
+            // We draw the orchestration time as part of the StartUp bar
+            ContainerdEvent::Orchestration => RGBColor(102, 102, 255),
+        }
+    }
+}
+
+/// Handle to a background thread that samples the resource usage of the
+/// containerd daemon until stopped
+pub struct ContainerdResourceSampler {
+    stop: Arc<AtomicBool>,
+    handle: thread::JoinHandle<Option<(u64, u64)>>,
+}
+
+impl ContainerdResourceSampler {
+    /// Stop sampling, and return the CPU time (in ms) consumed by containerd
+    /// while sampling, and its peak RSS (in KB). If we lost track of
+    /// containerd (e.g. it restarted while sampling), we return None
+    pub fn stop(self) -> Option<(u64, u64)> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle.join().ok().flatten()
+    }
+}
+
 #[derive(Debug)]
 pub struct Containerd {}
 
 impl Containerd {
-    // TODO: consider making this typed, or at least using the same strings
-    // below
-    pub const CONTAINERD_INFO_EVENTS: [&'static str; 7] = [
-        "StartUp",       // Fake event that we add to measure end-to-end time
-        "RunPodSandbox", // This event captures the time to start the sandbox
-        "PullImage",     // This event captures the time to pull an image in the host
-        "CreateContainerUserContainer",
-        "CreateContainerQueueProxy",
-        "StartContainerUserContainer", // For CoCo: pull app image in the guest
-        "StartContainerQueueProxy",    // For CoCo: pull sidecar image in the guest
-    ];
-
-    pub fn get_color_for_event(event: &str) -> RGBColor {
-        match event {
-            "StartUp" => RGBColor(102, 102, 255),
-            "RunPodSandbox" => RGBColor(102, 255, 178),
-            "PullImage" => RGBColor(245, 161, 66),
-            "CreateContainerUserContainer" => RGBColor(255, 102, 178),
-            "CreateContainerQueueProxy" => RGBColor(255, 102, 178),
-            "StartContainerUserContainer" => RGBColor(255, 255, 102),
-            "StartContainerQueueProxy" => RGBColor(255, 255, 102),
-            _ => panic!("{}(containerd): unrecognised event: {event}", Env::SYS_NAME),
-        }
+    // Log messages that the nydus-snapshotter emits when preparing, and
+    // mounting, a snapshot
+    const SNAPSHOTTER_PREPARE_MSG: &'static str = "[Prepare] snapshot with key";
+    const SNAPSHOTTER_MOUNTS_MSG: &'static str = "[Mounts] snapshot";
+
+    // Log message that nydusd (whose logs the nydus-snapshotter forwards to
+    // its own journal) emits when it fetches a blob's chunks from the backend
+    // on demand. The word boundary leaves out prefetching, which happens in
+    // the background, and is not on the start-up's critical path
+    const SNAPSHOTTER_LAZY_PULL_REGEX: &'static str =
+        r"(?i)\bfetch(ing)? chunks? from (the )?backend\b";
+
+    // Log messages that containerd's CRI plugin emits when a request returns.
+    // The wording is specific to the containerd version, so if it changes,
+    // this is the single place to update
+    const RETURNS_SANDBOX_ID_MSG: &'static str = "returns sandbox id";
+    const RETURNS_IMAGE_REFERENCE_MSG: &'static str = "returns image reference";
+    const RETURNS_CONTAINER_ID_MSG: &'static str = "returns container id";
+
+    // StartContainer returns no id, just "returns successfully". We match it
+    // loosely so that minor re-phrasings (e.g. "returned successfully") do not
+    // leave the event open forever
+    const RETURNS_SUCCESSFULLY_REGEX: &'static str = r"(?i)\breturn\w*\b.*\bsuccess";
+
+    // CreateContainer requests carry the container's metadata, from which we
+    // get the container's name (e.g. `&ContainerMetadata{Name:queue-proxy,`)
+    const CONTAINER_NAME_REGEX: &'static str = r"ContainerMetadata\{Name:(?P<name>[^,}]+)";
+
+    // Names of the two containers that Knative deploys in every pod. We time
+    // them separately, and add up the time of any other (sidecar) containers
+    const USER_CONTAINER_NAME: &'static str = "user-container";
+    const QUEUE_PROXY_NAME: &'static str = "queue-proxy";
+
+    // Log messages from the guest's attestation agent (which the Kata shim
+    // forwards to containerd's logs) while it attests with the KBS. We match
+    // the agent's name, and the KBS protocol's authentication and
+    // attestation endpoints that it logs the requests to
+    const ATTESTATION_REGEX: &'static str = r"\battestation-agent\b|/kbs/v0/(auth|attest)\b";
+
+    // The Kata shim tags every message that it forwards with its sandbox
+    const SHIM_SANDBOX_ID_REGEX: &'static str = r"\bsandbox=(?P<sbx_id>[a-fA-F0-9]+)";
+
+    const RESOURCE_SAMPLE_INTERVAL_MS: u64 = 100;
+
+    /// Get the pid of containerd's main process from its systemd unit. The
+    /// unit reports a pid of 0 if it is not running
+    fn get_containerd_pid() -> Option<u32> {
+        let output = Command::new("systemctl")
+            .args(["show", "--property", "MainPID", "--value"])
+            .arg(Env::containerd_unit())
+            .output()
+            .ok()?;
+
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .ok()
+            .filter(|pid| *pid != 0)
+    }
+
+    fn get_clock_ticks_per_sec() -> u64 {
+        let output = Command::new("getconf")
+            .arg("CLK_TCK")
+            .output()
+            .expect("sc2-exp(containerd): failed to spawn getconf command");
+
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .expect("sc2-exp(containerd): failed to parse CLK_TCK")
+    }
+
+    /// Read the user plus system CPU time, in clock ticks, from /proc/<pid>/stat
+    fn read_cpu_ticks(pid: u32) -> Option<u64> {
+        let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+
+        // The process name may contain spaces, so we only split the fields
+        // after it. After the name, utime and stime are fields 12 and 13
+        let fields: Vec<&str> = stat
+            .rsplit_once(')')
+            .map(|(_, rest)| rest)
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        let utime: u64 = fields.get(11)?.parse().ok()?;
+        let stime: u64 = fields.get(12)?.parse().ok()?;
+
+        Some(utime + stime)
+    }
+
+    /// Read the resident set size, in KB, from /proc/<pid>/status
+    fn read_rss_kb(pid: u32) -> Option<u64> {
+        fs::read_to_string(format!("/proc/{pid}/status"))
+            .ok()?
+            .lines()
+            .find(|line| line.starts_with("VmRSS:"))
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|rss| rss.parse().ok())
+    }
+
+    /// Start sampling containerd's CPU and memory usage in a background
+    /// thread. The sampling window ends when we call `stop` on the returned
+    /// handle. If we can not find containerd's process, we return None
+    pub fn start_resource_sampler() -> Option<ContainerdResourceSampler> {
+        let Some((pid, start_ticks)) =
+            Self::get_containerd_pid().and_then(|pid| Some((pid, Self::read_cpu_ticks(pid)?)))
+        else {
+            warn!(
+                "{}(containerd): failed to find the process of unit '{}', not sampling its resource usage",
+                Env::SYS_NAME,
+                Env::containerd_unit()
+            );
+            return None;
+        };
+        let ticks_per_sec = Self::get_clock_ticks_per_sec();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_clone = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            // If containerd goes away (or changes pid) mid-run, we can not
+            // tell how much it used, so we give up on the sample
+            let mut peak_rss_kb = Self::read_rss_kb(pid)?;
+            while !stop_clone.load(Ordering::Relaxed) {
+                thread::sleep(time::Duration::from_millis(
+                    Self::RESOURCE_SAMPLE_INTERVAL_MS,
+                ));
+                peak_rss_kb = peak_rss_kb.max(Self::read_rss_kb(pid)?);
+            }
+
+            let cpu_ms =
+                Self::read_cpu_ticks(pid)?.checked_sub(start_ticks)? * 1000 / ticks_per_sec;
+            debug!(
+                "{}(containerd): used {cpu_ms} ms of CPU (peak RSS: {peak_rss_kb} KB)",
+                Env::SYS_NAME
+            );
+
+            Some((cpu_ms, peak_rss_kb))
+        });
+
+        Some(ContainerdResourceSampler { stop, handle })
     }
 
     /// Parse timestamp from journalctl's JSON __REALTIME_TIMESTAMP
-    fn parse_timestamp(timestamp: &str) -> DateTime<Utc> {
-        let timestamp: i64 = timestamp.parse().unwrap();
-        let date_time_fixed = DateTime::from_timestamp_micros(timestamp)
-            .ok_or("sc2-exp: invalid timestamp")
-            .unwrap();
-        date_time_fixed.with_timezone(&Utc)
+    fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+        let timestamp: i64 = timestamp.parse().ok()?;
+        DateTime::from_timestamp_micros(timestamp)
+    }
+
+    /// Parse one line of journalctl's JSON output. journalctl may emit
+    /// non-JSON, or truncated, lines (e.g. if the journal rotates while we
+    /// read it), so we skip them (returning `Value::Null`) rather than
+    /// losing the whole run. If we fail to read the line, we return `None`
+    /// so that callers stop reading
+    fn parse_journalctl_line(line: io::Result<String>) -> Option<Value> {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                warn!(
+                    "{}(containerd): failed to read journalctl output: {e}",
+                    Env::SYS_NAME
+                );
+                return None;
+            }
+        };
+
+        match serde_json::from_str(&line) {
+            Ok(json) => Some(json),
+            Err(e) => {
+                debug!(
+                    "{}(containerd): skipping malformed journalctl line ({e}): {line}",
+                    Env::SYS_NAME
+                );
+                Some(Value::Null)
+            }
+        }
+    }
+
+    /// Spawn a journalctl process that dumps the logs for a systemd unit in
+    /// JSON format, either locally or on the given node. We only ask for the
+    /// entries since the cutoff time (rounded down to the second), so that
+    /// we do not re-scan the whole journal after each run
+    fn spawn_journalctl(
+        unit: &str,
+        cutoff_time: &DateTime<Utc>,
+        node: Option<&str>,
+    ) -> Result<Child, ContainerdError> {
+        K8s::get_node_sudo_cmd(node, "journalctl")
+            .args(["-xeu", unit, "-o", "json"])
+            .arg(format!("--since=@{}", cutoff_time.timestamp()))
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(ContainerdError::Journalctl)
+    }
+
+    /// Count the committed snapshots (i.e. the image layers) that a
+    /// snapshotter holds in the Kubernetes namespace (either locally or on
+    /// the given node). We skip the active snapshots, as each new container
+    /// gets one (its writable layer) even if its image is cached
+    pub fn count_snapshots(snapshotter: &str, node: Option<&str>) -> Result<usize, Box<dyn Error>> {
+        let output = K8s::get_node_sudo_cmd(node, "ctr")
+            .args([
+                "-n",
+                "k8s.io",
+                "snapshots",
+                "--snapshotter",
+                snapshotter,
+                "ls",
+            ])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "{}(containerd): failed to list {snapshotter} snapshots: error: {}",
+                Env::SYS_NAME,
+                String::from_utf8_lossy(&output.stderr)
+            )
+            .into());
+        }
+
+        // Skip the header line. Each line is `KEY PARENT KIND`, but the
+        // parent is empty for the bottom layer, so we check the last field
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .skip(1)
+            .filter(|line| line.split_whitespace().last() == Some("Committed"))
+            .count())
+    }
+
+    /// Get the size (in bytes) of the nydus-snapshotter's data directory
+    /// (either locally or on the given node). The SC2 baselines pull images
+    /// lazily, so the image store only knows about the image's manifest,
+    /// and not about the blobs that the snapshotter fetches
+    pub fn snapshotter_root_size_bytes(node: Option<&str>) -> Result<u64, Box<dyn Error>> {
+        let snapshotter_root = Env::snapshotter_root().display().to_string();
+        let output = K8s::get_node_sudo_cmd(node, "du")
+            .args(["--summarize", "--bytes", &snapshotter_root])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "{}(containerd): failed to get the size of {snapshotter_root}: error: {}",
+                Env::SYS_NAME,
+                String::from_utf8_lossy(&output.stderr)
+            )
+            .into());
+        }
+
+        // The output is `<SIZE> <PATH>`
+        String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .next()
+            .and_then(|size| size.parse::<u64>().ok())
+            .ok_or_else(|| {
+                format!(
+                    "{}(containerd): failed to parse the size of {snapshotter_root}: {}",
+                    Env::SYS_NAME,
+                    String::from_utf8_lossy(&output.stdout)
+                )
+                .into()
+            })
+    }
+
+    /// Return the timestamps for the events that we parse from the
+    /// nydus-snapshotter's logs.
+    ///
+    /// Preparing a snapshot for a pod involves preparing one snapshot per
+    /// container, so we measure SnapshotPrepare from the first prepare request
+    /// after the cutoff time, until the last prepare or mount request.
+    /// Similarly, we measure GuestLazyPull from the first to the last blob
+    /// fetch after the cutoff time. Only cold starts fetch blobs. We ignore
+    /// any entries after the end of the run, as the container may keep on
+    /// fetching blobs (e.g. once it serves the request) after it started.
+    pub fn get_snapshotter_events_from_journalctl(
+        cutoff_time: &DateTime<Utc>,
+        end_time: &DateTime<Utc>,
+        node: Option<&str>,
+    ) -> Result<EventTimestamps, ContainerdError> {
+        debug!(
+            "{}(containerd): parsing journalctl logs for nydus-snapshotter",
+            Env::SYS_NAME
+        );
+
+        let mut journalctl = Self::spawn_journalctl(Env::snapshotter_unit(), cutoff_time, node)?;
+        let stdout = journalctl.stdout.take().ok_or(ContainerdError::NoStdout)?;
+        let reader = BufReader::new(stdout);
+
+        let mut prepare_start: Option<DateTime<Utc>> = None;
+        let mut prepare_end: Option<DateTime<Utc>> = None;
+        let mut lazy_pull_start: Option<DateTime<Utc>> = None;
+        let mut lazy_pull_end: Option<DateTime<Utc>> = None;
+        let lazy_pull_regex = Regex::new(Self::SNAPSHOTTER_LAZY_PULL_REGEX).unwrap();
+        for line in reader.lines() {
+            let Some(json) = Self::parse_journalctl_line(line) else {
+                break;
+            };
+
+            if let (Some(timestamp), Some(message)) =
+                (json.get("__REALTIME_TIMESTAMP"), json.get("MESSAGE"))
+            {
+                let message = message.as_str().unwrap_or("");
+                let Some(timestamp) = Self::parse_timestamp(timestamp.as_str().unwrap_or(""))
+                else {
+                    continue;
+                };
+
+                // Skip log entries before the cutoff timestamp (journalctl's
+                // --since only filters with second granularity)
+                if timestamp < *cutoff_time || timestamp > *end_time {
+                    continue;
+                }
+
+                if message.contains(Self::SNAPSHOTTER_PREPARE_MSG) {
+                    if prepare_start.is_none() {
+                        prepare_start = Some(timestamp);
+                    }
+                    prepare_end = Some(timestamp);
+                } else if message.contains(Self::SNAPSHOTTER_MOUNTS_MSG) && prepare_start.is_some()
+                {
+                    prepare_end = Some(timestamp);
+                } else if lazy_pull_regex.is_match(message) {
+                    if lazy_pull_start.is_none() {
+                        lazy_pull_start = Some(timestamp);
+                    }
+                    lazy_pull_end = Some(timestamp);
+                }
+            }
+        }
+
+        journalctl.wait().map_err(ContainerdError::Journalctl)?;
+
+        let mut ts_map: EventTimestamps = BTreeMap::new();
+        match (prepare_start, prepare_end) {
+            (Some(start), Some(end)) => {
+                ts_map.insert(ContainerdEvent::SnapshotPrepare, (start, end));
+            }
+            _ => warn!(
+                "{}(containerd): did not find any snapshot preparation in nydus-snapshotter logs",
+                Env::SYS_NAME
+            ),
+        }
+        match (lazy_pull_start, lazy_pull_end) {
+            (Some(start), Some(end)) => {
+                ts_map.insert(ContainerdEvent::GuestLazyPull, (start, end));
+            }
+            _ => debug!(
+                "{}(containerd): did not find any lazy blob fetch in nydus-snapshotter logs",
+                Env::SYS_NAME
+            ),
+        }
+
+        Ok(ts_map)
     }
 
     /// Given a deployment id, return the timestamps for the RunPodSandbox
-    /// and the two CreateContainer and StartContainer events.
+    /// and the CreateContainer and StartContainer events of each container
+    /// (i.e. the user container, the queue proxy, and any sidecars). For the
+    /// confidential baselines, we also return the Attestation event, from the
+    /// first to the last attestation log message of the pod's sandbox.
+    /// Alongside the timestamps, we return the sandbox and container ids that
+    /// we found.
     ///
     /// This method is meant to be executed _without_ debug logging, and, for
     /// the time being, has a hardcoded number of events to parse. If we need
@@ -57,64 +637,105 @@ impl Containerd {
     ///
     /// Given that we may make measurements multiple times for each deployment
     /// id, we include a cutoff_time to discard entries prior to that timestamp.
+    /// If the deployment is pinned to a node, we read that node's logs, so
+    /// its clock must be in sync with ours.
+    ///
+    /// If we do not find all the events, we warn about it, unless `strict`,
+    /// in which case we return an error so that callers can discard the run.
     pub fn get_events_from_journalctl(
         deployment_id: &str,
         cutoff_time: &DateTime<Utc>,
-    ) -> BTreeMap<String, (DateTime<Utc>, DateTime<Utc>)> {
+        node: Option<&str>,
+        strict: bool,
+    ) -> Result<(EventTimestamps, PodIds), ContainerdError> {
         debug!(
             "{}(containerd): parsing journalctl logs for deployment: {deployment_id}",
             Env::SYS_NAME
         );
 
         // Load the journalctl output into a buffer reader
-        let mut journalctl = Command::new("sudo")
-            .args(["journalctl", "-xeu", "containerd", "-o", "json"])
-            .stdout(Stdio::piped())
-            .spawn()
-            .unwrap();
-        let stdout = journalctl
-            .stdout
-            .take()
-            .ok_or("sc2-exp: failed to open journalctl stdout")
-            .unwrap();
-        let reader = BufReader::new(stdout);
+        let mut journalctl = Self::spawn_journalctl(Env::containerd_unit(), cutoff_time, node)?;
+        let stdout = journalctl.stdout.take().ok_or(ContainerdError::NoStdout)?;
+        let events = Self::parse_events(BufReader::new(stdout), deployment_id, cutoff_time, strict);
+
+        // Always reap journalctl, even if we failed to parse its output, and
+        // only then return the events (or the parsing error)
+        let wait_result = journalctl.wait();
+        let events = events?;
+        wait_result.map_err(ContainerdError::Journalctl)?;
+
+        Ok(events)
+    }
 
+    /// Parse the start-up events for a deployment from containerd's logs, as
+    /// JSON entries (one per line) in journalctl's output format. We keep
+    /// this apart from reading the logs, so that we can also parse logs that
+    /// we have recorded elsewhere
+    pub fn parse_events(
+        reader: impl BufRead,
+        deployment_id: &str,
+        cutoff_time: &DateTime<Utc>,
+        strict: bool,
+    ) -> Result<(EventTimestamps, PodIds), ContainerdError> {
         // Prepare the output map
-        let mut ts_map: BTreeMap<String, (DateTime<Utc>, DateTime<Utc>)> = BTreeMap::new();
+        let mut ts_map: EventTimestamps = BTreeMap::new();
 
         // Helper start timestamps for different events
         let mut run_sandbox_start: Option<DateTime<Utc>> = None;
+        // First and last attestation messages of each sandbox (by id), as the
+        // Kata shim may forward them before we know our sandbox's id
+        let mut attestation_times: HashMap<String, (DateTime<Utc>, DateTime<Utc>)> = HashMap::new();
         let mut pull_image_start: Option<DateTime<Utc>> = None;
-        let mut user_container_start: Option<DateTime<Utc>> = None;
-        let mut queue_proxy_start: Option<DateTime<Utc>> = None;
-        let mut user_container_create: Option<DateTime<Utc>> = None;
-        let mut queue_proxy_create: Option<DateTime<Utc>> = None;
 
-        // Sandbox and container ids
+        // Sandbox id
         let mut sbx_id = String::new();
-        let mut user_container_id = String::new();
-        let mut queue_proxy_container_id = String::new();
+
+        // For each container in the pod, the timestamp of the CreateContainer
+        // request (by container name), the container's name (by container id)
+        // once the request returns, and the timestamp of the StartContainer
+        // request (by container id)
+        let mut container_creates: HashMap<String, DateTime<Utc>> = HashMap::new();
+        let mut container_names: HashMap<String, String> = HashMap::new();
+        let mut container_starts: HashMap<String, DateTime<Utc>> = HashMap::new();
+
+        // Time spent creating, and starting, each container (by name)
+        let mut create_times: BTreeMap<String, (DateTime<Utc>, DateTime<Utc>)> = BTreeMap::new();
+        let mut start_times: BTreeMap<String, (DateTime<Utc>, DateTime<Utc>)> = BTreeMap::new();
 
         // Regex expressions to get the sandbox/container ids
-        let sandbox_id_regex =
-            Regex::new(r#"returns sandbox id \\\"(?P<sbx_id>[a-fA-F0-9]+)\\\""#).unwrap();
-        let container_id_regex =
-            Regex::new(r#"returns container id \\\"(?P<ctr_id>[a-fA-F0-9]+)\\\""#).unwrap();
+        let sandbox_id_regex = Regex::new(&format!(
+            r#"{} \\\"(?P<sbx_id>[a-fA-F0-9]+)\\\""#,
+            regex::escape(Self::RETURNS_SANDBOX_ID_MSG)
+        ))
+        .unwrap();
+        let container_id_regex = Regex::new(&format!(
+            r#"{} \\\"(?P<ctr_id>[a-fA-F0-9]+)\\\""#,
+            regex::escape(Self::RETURNS_CONTAINER_ID_MSG)
+        ))
+        .unwrap();
+        let returns_successfully_regex = Regex::new(Self::RETURNS_SUCCESSFULLY_REGEX).unwrap();
+        let container_name_regex = Regex::new(Self::CONTAINER_NAME_REGEX).unwrap();
+        let attestation_regex = Regex::new(Self::ATTESTATION_REGEX).unwrap();
+        let shim_sandbox_id_regex = Regex::new(Self::SHIM_SANDBOX_ID_REGEX).unwrap();
 
         // Parse JSON log entries line by line
         for line in reader.lines() {
-            let line = line.unwrap();
-            let json: Value = serde_json::from_str(&line).unwrap();
+            let Some(json) = Self::parse_journalctl_line(line) else {
+                break;
+            };
 
             // Extract the timestamp and message fields from JSON
             if let (Some(timestamp), Some(message)) =
                 (json.get("__REALTIME_TIMESTAMP"), json.get("MESSAGE"))
             {
                 let message = message.as_str().unwrap_or("");
-                let timestamp = timestamp.as_str().unwrap_or("");
-                let timestamp = Self::parse_timestamp(timestamp);
+                let Some(timestamp) = Self::parse_timestamp(timestamp.as_str().unwrap_or(""))
+                else {
+                    continue;
+                };
 
-                // Skip log entries before the cutoff timestamp
+                // Skip log entries before the cutoff timestamp (journalctl's
+                // --since only filters with second granularity)
                 if timestamp < *cutoff_time {
                     continue;
                 }
@@ -129,17 +750,42 @@ impl Containerd {
                     continue;
                 }
 
-                if message.contains("RunPodSandbox") && message.contains("returns sandbox id") {
-                    if let Some(caps) = sandbox_id_regex.captures(message) {
-                        sbx_id = caps.name("sbx_id").unwrap().as_str().to_string();
+                // Other pods (e.g. of other deployments, or other replicas of
+                // ours) may start at the same time, so we only keep the first
+                // sandbox of our deployment
+                if sbx_id.is_empty()
+                    && message.contains("RunPodSandbox")
+                    && message.contains(deployment_id)
+                    && message.contains(Self::RETURNS_SANDBOX_ID_MSG)
+                {
+                    if let Some(id) = sandbox_id_regex
+                        .captures(message)
+                        .and_then(|caps| caps.name("sbx_id"))
+                    {
+                        sbx_id = id.as_str().to_string();
                         debug!("{}(containerd): got sandbox id: {sbx_id}", Env::SYS_NAME);
                         if let (Some(start), Some(end)) = (run_sandbox_start, Some(timestamp)) {
-                            ts_map.insert("RunPodSandbox".to_string(), (start, end));
+                            ts_map.insert(ContainerdEvent::RunPodSandbox, (start, end));
                         }
                         continue;
                     }
                 }
 
+                // ---------- Attestation ----------
+
+                if attestation_regex.is_match(message) {
+                    if let Some(id) = shim_sandbox_id_regex
+                        .captures(message)
+                        .and_then(|caps| caps.name("sbx_id"))
+                    {
+                        attestation_times
+                            .entry(id.as_str().to_string())
+                            .and_modify(|(_, end)| *end = timestamp)
+                            .or_insert((timestamp, timestamp));
+                    }
+                    continue;
+                }
+
                 // ---------- PullImage ----------
 
                 if pull_image_start.is_none() && message.contains("PullImage") {
@@ -148,11 +794,11 @@ impl Containerd {
                 }
 
                 if message.contains("PullImage")
-                    && message.contains("returns image reference")
+                    && message.contains(Self::RETURNS_IMAGE_REFERENCE_MSG)
                     && pull_image_start.is_some()
                 {
                     if let (Some(start), Some(end)) = (pull_image_start, Some(timestamp)) {
-                        ts_map.insert("PullImage".to_string(), (start, end));
+                        ts_map.insert(ContainerdEvent::PullImage, (start, end));
                     }
                     continue;
                 }
@@ -163,109 +809,149 @@ impl Containerd {
                     && message.contains("CreateContainer")
                     && message.contains(&sbx_id)
                 {
-                    // There are two CreateContainer events, one for the
-                    // user-container and one for the queue-proxy
-                    if message.contains("user-container") {
-                        // Start timestamp for CreateContainer in user-container
-                        if user_container_start.is_none() {
-                            user_container_start = Some(timestamp);
-                            continue;
-                        }
+                    // There is one CreateContainer event for each container
+                    // in the pod, that we tell apart by the container's name
+                    let Some(name) = container_name_regex
+                        .captures(message)
+                        .and_then(|caps| caps.name("name"))
+                    else {
+                        continue;
+                    };
+                    let name = name.as_str();
 
-                        // End timestamp and capture container ID
-                        if message.contains("returns container id") {
-                            if let Some(caps) = container_id_regex.captures(message) {
-                                user_container_id =
-                                    caps.name("ctr_id").unwrap().as_str().to_string();
-                                debug!(
-                                    "{}(containerd): got user container id: {user_container_id}",
-                                    Env::SYS_NAME
-                                );
-                                ts_map.insert(
-                                    "CreateContainerUserContainer".to_string(),
-                                    (user_container_start.unwrap(), timestamp),
-                                );
-                                user_container_start = None;
-                            }
-                        }
-                    } else if message.contains("queue-proxy") {
-                        // Start timestamp for CreateContainer in queue-proxy
-                        if queue_proxy_start.is_none() {
-                            queue_proxy_start = Some(timestamp);
+                    match container_creates.get(name).copied() {
+                        // Start timestamp for CreateContainer
+                        None => {
+                            container_creates.insert(name.to_string(), timestamp);
                             continue;
                         }
-
                         // End timestamp and capture container ID
-                        if message.contains("returns container id") {
-                            if let Some(caps) = container_id_regex.captures(message) {
-                                queue_proxy_container_id =
-                                    caps.name("ctr_id").unwrap().as_str().to_string();
+                        Some(start) if message.contains(Self::RETURNS_CONTAINER_ID_MSG) => {
+                            if let Some(id) = container_id_regex
+                                .captures(message)
+                                .and_then(|caps| caps.name("ctr_id"))
+                            {
                                 debug!(
-                                    "{}(containerd): got queue proxy id: {user_container_id}",
-                                    Env::SYS_NAME
-                                );
-                                ts_map.insert(
-                                    "CreateContainerQueueProxy".to_string(),
-                                    (queue_proxy_start.unwrap(), timestamp),
+                                    "{}(containerd): got container id for {name}: {}",
+                                    Env::SYS_NAME,
+                                    id.as_str()
                                 );
-                                queue_proxy_start = None;
+                                create_times.insert(name.to_string(), (start, timestamp));
+                                container_names.insert(id.as_str().to_string(), name.to_string());
+                                container_creates.remove(name);
                             }
                         }
+                        Some(_) => {}
                     }
                 }
 
                 // ---------- StartContainer ----------
 
                 if message.contains("StartContainer") {
-                    // There are two StartContainer events, one for the
-                    // user-container and one for the queue-proxy
-                    if message.contains(&user_container_id) {
-                        // Start timestamp for StartContainer in user-container
-                        if user_container_create.is_none() {
-                            user_container_create = Some(timestamp);
-                            continue;
-                        }
+                    // There is one StartContainer event for each container
+                    // that we have created, that we tell apart by its id
+                    let Some((id, name)) = container_names
+                        .iter()
+                        .find(|(id, _)| message.contains(id.as_str()))
+                    else {
+                        continue;
+                    };
 
-                        // End timestamp for StartContainer in user-container
-                        if message.contains("returns successfully") {
-                            ts_map.insert(
-                                "StartContainerUserContainer".to_string(),
-                                (user_container_create.unwrap(), timestamp),
-                            );
-                            user_container_create = None;
-                        }
-                    } else if message.contains(&queue_proxy_container_id) {
-                        // Start timestamp for StartContainer in queue-proxy
-                        if queue_proxy_create.is_none() {
-                            queue_proxy_create = Some(timestamp);
+                    match container_starts.get(id).copied() {
+                        // Start timestamp for StartContainer
+                        None => {
+                            container_starts.insert(id.clone(), timestamp);
                             continue;
                         }
-
-                        // End timestamp for StartContainer in queue-proxy
-                        if message.contains("returns successfully") {
-                            ts_map.insert(
-                                "StartContainerQueueProxy".to_string(),
-                                (queue_proxy_create.unwrap(), timestamp),
-                            );
-                            queue_proxy_create = None;
+                        // End timestamp for StartContainer
+                        Some(start) if returns_successfully_regex.is_match(message) => {
+                            start_times.insert(name.clone(), (start, timestamp));
+                            container_starts.remove(id);
                         }
+                        Some(_) => {}
                     }
                 }
             }
         }
 
-        // Wait on the process to silent clippy warning
-        journalctl
-            .wait()
-            .expect("Failed to wait on journalctl process");
+        if let Some((start, end)) = attestation_times.get(&sbx_id) {
+            ts_map.insert(ContainerdEvent::Attestation, (*start, *end));
+        }
+
+        // The user container and the queue proxy get their own events. For
+        // any other (sidecar) containers, we add up their times, and report
+        // them as an event that starts when the first sidecar does
+        for (times, user_container_event, queue_proxy_event, sidecars_event) in [
+            (
+                &create_times,
+                ContainerdEvent::CreateContainerUserContainer,
+                ContainerdEvent::CreateContainerQueueProxy,
+                ContainerdEvent::CreateContainerSidecars,
+            ),
+            (
+                &start_times,
+                ContainerdEvent::StartContainerUserContainer,
+                ContainerdEvent::StartContainerQueueProxy,
+                ContainerdEvent::StartContainerSidecars,
+            ),
+        ] {
+            let mut sidecars: Option<(DateTime<Utc>, chrono::Duration)> = None;
+            for (name, (start, end)) in times {
+                match name.as_str() {
+                    Self::USER_CONTAINER_NAME => {
+                        ts_map.insert(user_container_event, (*start, *end));
+                    }
+                    Self::QUEUE_PROXY_NAME => {
+                        ts_map.insert(queue_proxy_event, (*start, *end));
+                    }
+                    _ => {
+                        debug!(
+                            "{}(containerd): got sidecar container: {name}",
+                            Env::SYS_NAME
+                        );
+                        let (first_start, total) =
+                            sidecars.get_or_insert((*start, chrono::Duration::zero()));
+                        *first_start = (*first_start).min(*start);
+                        *total += *end - *start;
+                    }
+                }
+            }
+
+            if let Some((first_start, total)) = sidecars {
+                ts_map.insert(sidecars_event, (first_start, first_start + total));
+            }
+        }
+
+        // journalctl interleaves the entries of different processes, so a
+        // request may (rarely) return before it starts. We can not tell how
+        // long such an event took, so we treat it as missing
+        ts_map.retain(|event, (start, end)| {
+            if end < start {
+                warn!(
+                    "{}(containerd): dropping {event} for '{deployment_id}' as it ends before it starts",
+                    Env::SYS_NAME
+                );
+                return false;
+            }
+            true
+        });
 
         debug!(
             "{}(containerd): got a total of {} events",
             Env::SYS_NAME,
             ts_map.len()
         );
-        let num_expected_events = 6;
-        if ts_map.len() == (num_expected_events - 1) && pull_image_start.is_none() {
+        // We parse all events except for the end-to-end StartUp time, and
+        // the events that only some baselines, or services, report
+        let is_required =
+            |event: &ContainerdEvent| *event != ContainerdEvent::StartUp && !event.is_optional();
+        let num_expected_events = ContainerdEvent::iter_variants()
+            .filter(|event| is_required(event))
+            .count();
+        let num_events = ts_map.keys().filter(|event| is_required(event)).count();
+        let misses_pull_image =
+            num_events == (num_expected_events - 1) && pull_image_start.is_none();
+        if let (true, Some(run_sandbox_start)) = (misses_pull_image, run_sandbox_start) {
             // Warm Knative starts do not report the PullImage event, so we
             // add it here with the same start/end timestamp so that it reports
             // a time of 0
@@ -274,15 +960,290 @@ impl Containerd {
                 Env::SYS_NAME
             );
             ts_map.insert(
-                "PullImage".to_string(),
-                (run_sandbox_start.unwrap(), run_sandbox_start.unwrap()),
+                ContainerdEvent::PullImage,
+                (run_sandbox_start, run_sandbox_start),
             );
-        } else if ts_map.len() != num_expected_events {
-            warn!("{}(containerd): expected {num_expected_events} journalctl events for '{deployment_id}' but got {}",
-                  Env::SYS_NAME,
-                  ts_map.len());
+        } else if num_events != num_expected_events {
+            warn!("{}(containerd): expected {num_expected_events} journalctl events for '{deployment_id}' but got {num_events}",
+                  Env::SYS_NAME);
+            if strict {
+                return Err(ContainerdError::MissingEvents {
+                    expected: num_expected_events,
+                    got: num_events,
+                });
+            }
+        }
+
+        let pod_ids = PodIds {
+            sandbox_id: Some(sbx_id).filter(|id| !id.is_empty()),
+            container_ids: container_names
+                .into_iter()
+                .map(|(id, name)| (name, id))
+                .collect(),
+        };
+
+        Ok((ts_map, pod_ids))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DEPLOYMENT_ID: &str = "helloworld-py-00001-deployment";
+    const SBX_ID: &str = "aaaa1111";
+    const USER_CTR_ID: &str = "bbbb2222";
+    const QUEUE_PROXY_CTR_ID: &str = "cccc3333";
+
+    // All timestamps in the fixtures are in ms after the cutoff time
+    fn cutoff_time() -> DateTime<Utc> {
+        DateTime::from_timestamp_micros(1_700_000_000_000_000).unwrap()
+    }
+
+    fn ts(ms: i64) -> DateTime<Utc> {
+        cutoff_time() + chrono::Duration::milliseconds(ms)
+    }
+
+    /// Turn (timestamp, message) pairs into journalctl's JSON output
+    fn to_journal(entries: &[(i64, String)]) -> Vec<u8> {
+        entries
+            .iter()
+            .map(|(ms, message)| {
+                json!({
+                    "__REALTIME_TIMESTAMP": ts(*ms).timestamp_micros().to_string(),
+                    "MESSAGE": message,
+                })
+                .to_string()
+                    + "\n"
+            })
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    fn run_sandbox(ms: i64, pod: &str, sbx_id: Option<&str>) -> (i64, String) {
+        let message = format!(
+            "RunPodSandbox for &PodSandboxMetadata{{Name:{pod},Namespace:default,Attempt:0,}}"
+        );
+        match sbx_id {
+            Some(sbx_id) => (ms, format!(r#"{message} returns sandbox id \"{sbx_id}\""#)),
+            None => (ms, message),
         }
+    }
+
+    fn create_container(ms: i64, sbx_id: &str, name: &str, ctr_id: Option<&str>) -> (i64, String) {
+        let message = format!(
+            r#"CreateContainer within sandbox \"{sbx_id}\" for &ContainerMetadata{{Name:{name},Attempt:0,}}"#
+        );
+        match ctr_id {
+            Some(ctr_id) => (
+                ms,
+                format!(r#"{message} returns container id \"{ctr_id}\""#),
+            ),
+            None => (ms, message),
+        }
+    }
+
+    fn start_container(ms: i64, ctr_id: &str, returns: bool) -> (i64, String) {
+        let message = format!(r#"StartContainer for \"{ctr_id}\""#);
+        if returns {
+            (ms, format!("{message} returns successfully"))
+        } else {
+            (ms, message)
+        }
+    }
+
+    /// Log entries of a cold start of our deployment
+    fn cold_start() -> Vec<(i64, String)> {
+        let pod = format!("{DEPLOYMENT_ID}-7f9c-abcde");
+        vec![
+            run_sandbox(0, &pod, None),
+            run_sandbox(100, &pod, Some(SBX_ID)),
+            (110, r#"PullImage \"registry/helloworld-py:unencrypted\""#.to_string()),
+            (
+                300,
+                r#"PullImage \"registry/helloworld-py:unencrypted\" returns image reference \"sha256:dddd\""#
+                    .to_string(),
+            ),
+            create_container(310, SBX_ID, "user-container", None),
+            create_container(350, SBX_ID, "user-container", Some(USER_CTR_ID)),
+            start_container(360, USER_CTR_ID, false),
+            start_container(400, USER_CTR_ID, true),
+            create_container(410, SBX_ID, "queue-proxy", None),
+            create_container(440, SBX_ID, "queue-proxy", Some(QUEUE_PROXY_CTR_ID)),
+            start_container(450, QUEUE_PROXY_CTR_ID, false),
+            start_container(480, QUEUE_PROXY_CTR_ID, true),
+        ]
+    }
+
+    fn parse(
+        entries: &[(i64, String)],
+        strict: bool,
+    ) -> Result<(EventTimestamps, PodIds), ContainerdError> {
+        Containerd::parse_events(
+            to_journal(entries).as_slice(),
+            DEPLOYMENT_ID,
+            &cutoff_time(),
+            strict,
+        )
+    }
+
+    #[test]
+    fn parses_all_the_events_of_a_cold_start() {
+        let (event_ts, pod_ids) = parse(&cold_start(), true).unwrap();
+
+        assert_eq!(event_ts[&ContainerdEvent::RunPodSandbox], (ts(0), ts(100)));
+        assert_eq!(event_ts[&ContainerdEvent::PullImage], (ts(110), ts(300)));
+        assert_eq!(
+            event_ts[&ContainerdEvent::CreateContainerUserContainer],
+            (ts(310), ts(350))
+        );
+        assert_eq!(
+            event_ts[&ContainerdEvent::StartContainerUserContainer],
+            (ts(360), ts(400))
+        );
+        assert_eq!(
+            event_ts[&ContainerdEvent::CreateContainerQueueProxy],
+            (ts(410), ts(440))
+        );
+        assert_eq!(
+            event_ts[&ContainerdEvent::StartContainerQueueProxy],
+            (ts(450), ts(480))
+        );
+        assert_eq!(pod_ids.sandbox_id.as_deref(), Some(SBX_ID));
+        assert_eq!(pod_ids.container_ids["user-container"], USER_CTR_ID);
+        assert_eq!(pod_ids.container_ids["queue-proxy"], QUEUE_PROXY_CTR_ID);
+    }
+
+    #[test]
+    fn warm_start_gets_an_empty_pull_image() {
+        let entries: Vec<_> = cold_start()
+            .into_iter()
+            .filter(|(_, message)| !message.contains("PullImage"))
+            .collect();
+        let (event_ts, _) = parse(&entries, true).unwrap();
+
+        assert_eq!(event_ts[&ContainerdEvent::PullImage], (ts(0), ts(0)));
+    }
+
+    #[test]
+    fn missing_events_are_only_an_error_if_strict() {
+        // The queue proxy never returns from StartContainer
+        let mut entries = cold_start();
+        entries.pop();
+
+        let (event_ts, _) = parse(&entries, false).unwrap();
+        assert!(!event_ts.contains_key(&ContainerdEvent::StartContainerQueueProxy));
+
+        assert!(matches!(
+            parse(&entries, true),
+            Err(ContainerdError::MissingEvents {
+                expected: 6,
+                got: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn skips_out_of_order_entries_before_the_cutoff() {
+        // A late entry from a previous run of the same deployment must not
+        // re-open any event
+        let mut entries = cold_start();
+        entries.insert(
+            3,
+            run_sandbox(-1000, &format!("{DEPLOYMENT_ID}-old"), Some("eeee4444")),
+        );
+        entries.insert(6, start_container(-900, USER_CTR_ID, false));
+
+        let (event_ts, pod_ids) = parse(&entries, true).unwrap();
+        assert_eq!(event_ts[&ContainerdEvent::RunPodSandbox], (ts(0), ts(100)));
+        assert_eq!(
+            event_ts[&ContainerdEvent::StartContainerUserContainer],
+            (ts(360), ts(400))
+        );
+        assert_eq!(pod_ids.sandbox_id.as_deref(), Some(SBX_ID));
+    }
+
+    #[test]
+    fn drops_events_that_end_before_they_start() {
+        let mut entries = cold_start();
+        let user_container_start = entries.iter_mut().find(|(ms, _)| *ms == 400).unwrap();
+        user_container_start.0 = 355;
+
+        let (event_ts, _) = parse(&entries, false).unwrap();
+        assert!(!event_ts.contains_key(&ContainerdEvent::StartContainerUserContainer));
+        assert!(parse(&entries, true).is_err());
+    }
+
+    #[test]
+    fn scopes_attestation_to_our_sandbox() {
+        let attestation = |ms: i64, sbx_id: &str, path: &str| {
+            (
+                ms,
+                format!(
+                    "time=\"2024-01-01T00:00:00Z\" level=info msg=\"POST https://kbs:8080{path}\" name=containerd-shim-v2 sandbox={sbx_id} source=agent"
+                ),
+            )
+        };
+
+        // Our guest attests while pulling the image, and another one at the
+        // same time. Messages that only mention the KBS are not attestation
+        let mut entries = cold_start();
+        entries.insert(7, attestation(365, SBX_ID, "/kbs/v0/auth"));
+        entries.insert(8, attestation(370, "ffff5555", "/kbs/v0/auth"));
+        entries.insert(9, attestation(380, SBX_ID, "/kbs/v0/attest"));
+        entries.insert(10, attestation(385, SBX_ID, "/kbs/v0/resource"));
+        entries.insert(11, attestation(390, "ffff5555", "/kbs/v0/attest"));
+
+        let (event_ts, _) = parse(&entries, true).unwrap();
+        assert_eq!(event_ts[&ContainerdEvent::Attestation], (ts(365), ts(380)));
+    }
+
+    #[test]
+    fn keeps_the_first_sandbox_of_our_deployment() {
+        // Another deployment's sandbox returns first, and a second replica of
+        // ours starts while we are creating the containers
+        let mut entries = cold_start();
+        entries.insert(
+            1,
+            run_sandbox(50, "other-00001-deployment-1234", Some("ffff5555")),
+        );
+        entries.insert(
+            7,
+            run_sandbox(320, &format!("{DEPLOYMENT_ID}-7f9c-fghij"), None),
+        );
+        entries.insert(
+            8,
+            run_sandbox(
+                330,
+                &format!("{DEPLOYMENT_ID}-7f9c-fghij"),
+                Some("99998888"),
+            ),
+        );
+        entries.push(create_container(490, "99998888", "user-container", None));
+        entries.push(create_container(
+            520,
+            "99998888",
+            "user-container",
+            Some("77776666"),
+        ));
+
+        let (event_ts, pod_ids) = parse(&entries, true).unwrap();
+        assert_eq!(event_ts[&ContainerdEvent::RunPodSandbox], (ts(0), ts(100)));
+        assert_eq!(
+            event_ts[&ContainerdEvent::CreateContainerUserContainer],
+            (ts(310), ts(350))
+        );
+        assert_eq!(pod_ids.sandbox_id.as_deref(), Some(SBX_ID));
+        assert_eq!(pod_ids.container_ids["user-container"], USER_CTR_ID);
+    }
 
-        ts_map
+    #[test]
+    fn lazy_pull_only_matches_on_demand_fetches() {
+        let lazy_pull_regex = Regex::new(Containerd::SNAPSHOTTER_LAZY_PULL_REGEX).unwrap();
+        assert!(lazy_pull_regex.is_match("fetch chunks from backend, blob 1a2b3c"));
+        assert!(lazy_pull_regex.is_match("Fetching chunk from the backend"));
+        assert!(!lazy_pull_regex.is_match("prefetch chunks from backend, blob 1a2b3c"));
+        assert!(!lazy_pull_regex.is_match("blob 1a2b3c not fetched yet, skip it"));
     }
 }
@@ -1,18 +1,197 @@
-use crate::env::Env;
-use log::debug;
-use std::{error::Error, process::Command, process::Stdio, str};
+use crate::{env::Env, kubernetes::K8s};
+use clap::ValueEnum;
+use log::{debug, warn};
+use serde_json::Value;
+use std::{
+    error::Error,
+    fmt,
+    process::{Output, Stdio},
+    str,
+};
 
+/// An image in the CRI's image store, as reported by the CRI backend
 #[derive(Debug)]
-pub struct Cri {}
+pub struct CriImage {
+    pub name: String,
+    pub tag: String,
+    pub id: String,
+}
 
-impl Cri {
-    /// Get an image's digest from its tag using `crictl images`
-    fn get_digest_from_tag(image_tag: &str) -> Result<Vec<String>, Box<dyn Error>> {
-        // Get the list of images in crictl
-        let image_ids_output = Command::new("sudo")
-            .arg("crictl")
+/// Errors removing images from the CRI's image store
+#[derive(Debug)]
+pub enum CriError {
+    // Something (e.g. containerd) still holds a reference to the image
+    ImageInUse(String),
+    // The image is not in the store (e.g. it was already removed)
+    ImageNotFound(String),
+    Command(String),
+}
+
+impl CriError {
+    /// Whether the error is benign, and we can carry on with the experiment
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, CriError::ImageInUse(_) | CriError::ImageNotFound(_))
+    }
+
+    /// How bad the error is, so that, when we hit many errors, we report the
+    /// worst one (e.g. a failed command over an image that was not there)
+    fn severity(&self) -> u8 {
+        match self {
+            CriError::ImageNotFound(_) => 0,
+            CriError::ImageInUse(_) => 1,
+            CriError::Command(_) => 2,
+        }
+    }
+
+    /// Merge a new error into a result, keeping the most severe error (or
+    /// the first one, if they are equally severe)
+    fn merge_into(self, result: Result<(), CriError>) -> Result<(), CriError> {
+        match result {
+            Err(e) if e.severity() >= self.severity() => Err(e),
+            _ => Err(self),
+        }
+    }
+}
+
+impl fmt::Display for CriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CriError::ImageInUse(image) => write!(f, "image {image} is still in use"),
+            CriError::ImageNotFound(image) => write!(f, "image {image} not found"),
+            CriError::Command(e) => write!(f, "CRI command failed: {e}"),
+        }
+    }
+}
+
+impl Error for CriError {}
+
+/// Interface to the CRI runtime that we interact with when running the
+/// experiments (e.g. to clean the image store between cold starts)
+pub trait CriBackend {
+    /// List all images in the CRI's image store
+    fn list_images(&self) -> Result<Vec<CriImage>, Box<dyn Error>>;
+
+    /// Remove an image from the CRI's image store given its id
+    fn remove_image_by_id(&self, image_id: &str) -> Result<(), CriError>;
+
+    /// Pull an image into the CRI's image store given its tag
+    fn pull_image(&self, image_tag: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Get the size (in bytes) of each image in the CRI's image store
+    fn list_image_sizes(&self) -> Result<Vec<u64>, Box<dyn Error>>;
+}
+
+/// CRI clients that we can manage the image store with
+#[derive(Clone, Debug, ValueEnum)]
+pub enum ContainerRuntime {
+    Crictl,
+    Nerdctl,
+}
+
+impl ContainerRuntime {
+    /// Get the CRI backend for this client, against the given runtime
+    /// endpoint, either locally or on a given node
+    pub fn get_backend(&self, runtime_endpoint: &str, node: Option<&str>) -> Box<dyn CriBackend> {
+        match self {
+            ContainerRuntime::Crictl => Box::new(Crictl::new(runtime_endpoint, node)),
+            ContainerRuntime::Nerdctl => Box::new(Nerdctl::new(runtime_endpoint, node)),
+        }
+    }
+}
+
+/// Turn the output of a command to remove an image into a Result, telling
+/// benign errors (the image is in use, or not found) apart from the rest
+fn check_rmi_output(image_id: &str, output: &Output) -> Result<(), CriError> {
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lower_stderr = stderr.to_lowercase();
+    if lower_stderr.contains("in use") || lower_stderr.contains("being used") {
+        return Err(CriError::ImageInUse(image_id.to_string()));
+    }
+    // crictl and nerdctl report a missing image as `no such image <ref>`,
+    // whereas containerd's CRI plugin reports it as `image "<ref>" not found`
+    if lower_stderr.contains("no such image")
+        || lower_stderr.contains(&format!("image \"{}\" not found", image_id.to_lowercase()))
+    {
+        return Err(CriError::ImageNotFound(image_id.to_string()));
+    }
+
+    Err(CriError::Command(match output.status.code() {
+        Some(code) => format!("cri-rmi exited with error (code: {code}): {stderr}"),
+        None => format!("cri-rmi command failed: {stderr}"),
+    }))
+}
+
+/// Turn the output of a command to pull an image into a Result
+fn check_pull_output(image_tag: &str, output: &Output) -> Result<(), Box<dyn Error>> {
+    if !output.status.success() {
+        return Err(format!(
+            "{}(cri): failed to pull image {image_tag}: error: {}",
+            Env::SYS_NAME,
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Parse a human-readable size (e.g. `12.3MB` or `12.3 MiB`) into bytes
+fn parse_human_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let unit_idx = size
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(size.len());
+    let (value, unit) = size.split_at(unit_idx);
+    let multiplier: f64 = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "kb" => 1e3,
+        "mb" => 1e6,
+        "gb" => 1e9,
+        "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0_f64.powi(2),
+        "gib" => 1024.0_f64.powi(3),
+        "tib" => 1024.0_f64.powi(4),
+        _ => return None,
+    };
+
+    Some((value.trim().parse::<f64>().ok()? * multiplier) as u64)
+}
+
+/// Default CRI backend, that uses `crictl` against a CRI runtime endpoint
+/// (containerd's socket by default), either locally or on a given node
+#[derive(Debug)]
+pub struct Crictl {
+    runtime_endpoint: String,
+    node: Option<String>,
+}
+
+impl Crictl {
+    pub const DEFAULT_RUNTIME_ENDPOINT: &'static str = "unix:///run/containerd/containerd.sock";
+
+    pub fn new(runtime_endpoint: &str, node: Option<&str>) -> Self {
+        Self {
+            runtime_endpoint: runtime_endpoint.to_string(),
+            node: node.map(str::to_string),
+        }
+    }
+}
+
+impl Default for Crictl {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_RUNTIME_ENDPOINT, None)
+    }
+}
+
+impl CriBackend for Crictl {
+    fn list_images(&self) -> Result<Vec<CriImage>, Box<dyn Error>> {
+        let image_ids_output = K8s::get_node_sudo_cmd(self.node.as_deref(), "crictl")
             .arg("--runtime-endpoint")
-            .arg("unix:///run/containerd/containerd.sock")
+            .arg(&self.runtime_endpoint)
             .arg("images")
             .stdout(Stdio::piped())
             .output()
@@ -27,70 +206,273 @@ impl Cri {
             .into());
         }
 
-        // We deliberately only filter by image name, and not by tag, as
-        // somtimes the tag appears as none, this means that we may sometimes
-        // remove more images than needed, but we are ok with that
-        let (image_name, _tag) = image_tag.split_once(':').unwrap();
+        // Each line (after the header) is: IMAGE TAG IMAGE_ID SIZE
         let image_ids = String::from_utf8_lossy(&image_ids_output.stdout);
-        let filtered_image_ids: Vec<String> = image_ids
+        Ok(image_ids
             .lines()
-            .filter(|line| line.contains(image_name))
-            // .filter(|line| line.contains(tag))
-            .filter_map(|line| line.split_whitespace().nth(2))
-            .map(|s| s.to_string())
-            .collect();
+            .skip(1)
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                Some(CriImage {
+                    name: fields.next()?.to_string(),
+                    tag: fields.next()?.to_string(),
+                    id: fields.next()?.to_string(),
+                })
+            })
+            .collect())
+    }
 
-        if filtered_image_ids.is_empty() {
+    fn remove_image_by_id(&self, image_id: &str) -> Result<(), CriError> {
+        let output = K8s::get_node_sudo_cmd(self.node.as_deref(), "crictl")
+            .args([
+                "--runtime-endpoint",
+                &self.runtime_endpoint,
+                "rmi",
+                image_id,
+            ])
+            .output()
+            .expect("sc2-exp(cri): error removing image");
+
+        check_rmi_output(image_id, &output)
+    }
+
+    fn pull_image(&self, image_tag: &str) -> Result<(), Box<dyn Error>> {
+        let output = K8s::get_node_sudo_cmd(self.node.as_deref(), "crictl")
+            .args([
+                "--runtime-endpoint",
+                &self.runtime_endpoint,
+                "pull",
+                image_tag,
+            ])
+            .output()?;
+
+        check_pull_output(image_tag, &output)
+    }
+
+    fn list_image_sizes(&self) -> Result<Vec<u64>, Box<dyn Error>> {
+        let output = K8s::get_node_sudo_cmd(self.node.as_deref(), "crictl")
+            .args([
+                "--runtime-endpoint",
+                &self.runtime_endpoint,
+                "images",
+                "--output",
+                "json",
+            ])
+            .output()?;
+
+        if !output.status.success() {
             return Err(format!(
-                "{}(cri): did not find any matching image ids for image: {image_tag}",
+                "{}(cri): failed to get crictl images: error: {}",
                 Env::SYS_NAME,
+                String::from_utf8_lossy(&output.stderr)
             )
             .into());
         }
 
+        // In JSON format, crictl reports the exact size (in bytes) of each
+        // image, as a string
+        let images: Value = serde_json::from_slice(&output.stdout)?;
+        Ok(images["images"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|image| image["size"].as_str()?.parse::<u64>().ok())
+            .collect())
+    }
+}
+
+/// CRI backend that uses `nerdctl` against containerd's socket (in the
+/// namespace where Kubernetes keeps its images), either locally or on a
+/// given node
+#[derive(Debug)]
+pub struct Nerdctl {
+    address: String,
+    node: Option<String>,
+}
+
+impl Nerdctl {
+    const CONTAINERD_NAMESPACE: &'static str = "k8s.io";
+
+    pub fn new(address: &str, node: Option<&str>) -> Self {
+        Self {
+            address: address.to_string(),
+            node: node.map(str::to_string),
+        }
+    }
+
+    fn get_cmd(&self, args: &[&str]) -> std::process::Command {
+        let mut cmd = K8s::get_node_sudo_cmd(self.node.as_deref(), "nerdctl");
+        cmd.args([
+            "--address",
+            &self.address,
+            "--namespace",
+            Self::CONTAINERD_NAMESPACE,
+        ])
+        .args(args);
+        cmd
+    }
+}
+
+impl CriBackend for Nerdctl {
+    fn list_images(&self) -> Result<Vec<CriImage>, Box<dyn Error>> {
+        let images_output = self
+            .get_cmd(&["images", "--format", "{{.Repository}} {{.Tag}} {{.ID}}"])
+            .stdout(Stdio::piped())
+            .output()
+            .expect("sc2(cri): failed to execute nerdctl images command");
+
+        if !images_output.status.success() {
+            return Err(format!(
+                "{}(cri): failed to get nerdctl images: error: {}",
+                Env::SYS_NAME,
+                String::from_utf8_lossy(&images_output.stderr)
+            )
+            .into());
+        }
+
+        // Each line (without a header) is: REPOSITORY TAG IMAGE_ID
+        let images = String::from_utf8_lossy(&images_output.stdout);
+        Ok(images
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                Some(CriImage {
+                    name: fields.next()?.to_string(),
+                    tag: fields.next()?.to_string(),
+                    id: fields.next()?.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    fn remove_image_by_id(&self, image_id: &str) -> Result<(), CriError> {
+        let output = self
+            .get_cmd(&["rmi", "--force", image_id])
+            .output()
+            .expect("sc2-exp(cri): error removing image");
+
+        check_rmi_output(image_id, &output)
+    }
+
+    fn pull_image(&self, image_tag: &str) -> Result<(), Box<dyn Error>> {
+        let output = self.get_cmd(&["pull", "--quiet", image_tag]).output()?;
+
+        check_pull_output(image_tag, &output)
+    }
+
+    fn list_image_sizes(&self) -> Result<Vec<u64>, Box<dyn Error>> {
+        let output = self
+            .get_cmd(&["images", "--format", "{{.Size}}"])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "{}(cri): failed to get nerdctl images: error: {}",
+                Env::SYS_NAME,
+                String::from_utf8_lossy(&output.stderr)
+            )
+            .into());
+        }
+
+        // nerdctl only reports human-readable sizes (e.g. `12.3 MiB`). We
+        // skip the ones that we can not parse, but warn about them, as the
+        // total size is then an underestimate
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let size = parse_human_size(line);
+                if size.is_none() {
+                    warn!(
+                        "{}(cri): skipping image with unknown size: {line}",
+                        Env::SYS_NAME
+                    );
+                }
+                size
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug)]
+pub struct Cri {}
+
+impl Cri {
+    /// Get an image's digest from its tag, searching a list of images in the
+    /// CRI's image store (so that, when looking up many tags, we only list
+    /// the images once)
+    fn get_digest_from_tag(images: &[CriImage], image_tag: &str) -> Result<Vec<String>, CriError> {
+        // We deliberately only filter by image name, and not by tag, as
+        // somtimes the tag appears as none, this means that we may sometimes
+        // remove more images than needed, but we are ok with that
+        let (image_name, _tag) = image_tag.split_once(':').unwrap();
+        let filtered_image_ids: Vec<String> = images
+            .iter()
+            .filter(|image| image.name.contains(image_name))
+            .map(|image| image.id.clone())
+            .collect();
+
+        if filtered_image_ids.is_empty() {
+            return Err(CriError::ImageNotFound(image_tag.to_string()));
+        }
+
         // Extract and return the digest
-        Ok(filtered_image_ids.clone())
+        Ok(filtered_image_ids)
     }
 
     /// Remove an image from the CRI's image store. Note that removing the
     /// image from tag is, sometimes, unreliable, so we remove it by specifying
     /// its digest. Furthermore, tags do not always appear in crictl images,
-    /// so we remove all tags of the same image.
-    pub fn remove_image(image_tag: String) {
-        let image_digests = Self::get_digest_from_tag(&image_tag).unwrap();
-        for image_digest in &image_digests {
-            debug!(
-                "{}(cri): removing image {image_tag} (sha: {image_digest})",
-                Env::SYS_NAME
-            );
-
-            let output = Command::new("sudo")
-                .args([
-                    "crictl",
-                    "--runtime-endpoint",
-                    "unix:///run/containerd/containerd.sock",
-                    "rmi",
-                    image_digest,
-                ])
-                .output()
-                .expect("sc2-exp(cri): error removing image");
-
-            match output.status.code() {
-                Some(0) => {}
-                Some(code) => {
-                    let stderr = str::from_utf8(&output.stderr)
-                        .unwrap_or("sc2-exp(cri): failed to get stderr");
-                    panic!(
-                        "{}(cri): cri-rmi exited with error (code: {code}): {stderr}",
-                        Env::SYS_NAME
-                    );
-                }
-                None => {
-                    let stderr = str::from_utf8(&output.stderr)
-                        .unwrap_or("sc2-exp(cri): failed to get stderr");
-                    panic!("{}(cri): cri-rmi command failed: {stderr}", Env::SYS_NAME);
+    /// so we remove all tags of the same image. If we fail to remove one of
+    /// them, we still try to remove the rest, and return the most severe
+    /// error.
+    pub fn remove_image(cri: &dyn CriBackend, image_tag: String) -> Result<(), CriError> {
+        Self::remove_images(cri, &[image_tag])
+    }
+
+    /// Remove many images from the CRI's image store, listing the images
+    /// only once. As with `remove_image`, we try to remove all of them, and
+    /// return the most severe error
+    pub fn remove_images(cri: &dyn CriBackend, image_tags: &[String]) -> Result<(), CriError> {
+        let images = cri
+            .list_images()
+            .map_err(|e| CriError::Command(e.to_string()))?;
+
+        let mut result = Ok(());
+        for image_tag in image_tags {
+            let image_digests = match Self::get_digest_from_tag(&images, image_tag) {
+                Ok(image_digests) => image_digests,
+                Err(e) => {
+                    result = e.merge_into(result);
+                    continue;
                 }
             };
+
+            for image_digest in &image_digests {
+                debug!(
+                    "{}(cri): removing image {image_tag} (sha: {image_digest})",
+                    Env::SYS_NAME
+                );
+
+                if let Err(e) = cri.remove_image_by_id(image_digest) {
+                    result = e.merge_into(result);
+                }
+            }
         }
+
+        result
+    }
+
+    /// Pull an image into the CRI's image store, so that the next start-up
+    /// finds it there regardless of what previous runs left behind
+    pub fn pull_image(cri: &dyn CriBackend, image_tag: &str) -> Result<(), Box<dyn Error>> {
+        debug!("{}(cri): pulling image {image_tag}", Env::SYS_NAME);
+        cri.pull_image(image_tag)
+    }
+
+    /// Get the total size (in bytes) of all the images in the CRI's image
+    /// store, to tell how much of an image a run actually materialized
+    pub fn image_cache_size_bytes(cri: &dyn CriBackend) -> Result<u64, Box<dyn Error>> {
+        Ok(cri.list_image_sizes()?.iter().sum())
     }
 }
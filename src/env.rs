@@ -1,23 +1,174 @@
-use std::{env, path::PathBuf};
+use serde::Deserialize;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+static ENV_CONFIG: OnceLock<EnvConfig> = OnceLock::new();
+
+/// Deployment-specific settings, that we read from a TOML config file and
+/// override with env. vars, falling back to the defaults in `Env`
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnvConfig {
+    pub container_registry_url: String,
+    pub k8s_namespace: String,
+    pub containerd_unit: String,
+    pub snapshotter_unit: String,
+    pub snapshotter_root: PathBuf,
+    pub results_root: Option<PathBuf>,
+    pub apps_root: Option<PathBuf>,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            container_registry_url: Env::DEFAULT_CONTAINER_REGISTRY_URL.to_string(),
+            k8s_namespace: Env::DEFAULT_K8S_NAMESPACE.to_string(),
+            containerd_unit: Env::DEFAULT_CONTAINERD_UNIT.to_string(),
+            snapshotter_unit: Env::DEFAULT_SNAPSHOTTER_UNIT.to_string(),
+            snapshotter_root: PathBuf::from(Env::DEFAULT_SNAPSHOTTER_ROOT),
+            results_root: None,
+            apps_root: None,
+        }
+    }
+}
 
 pub struct Env {}
 
 impl Env {
-    pub const CONTAINER_REGISTRY_URL: &'static str = "sc2cr.io/applications";
-    pub const K8S_NAMESPACE: &'static str = "sc2";
+    pub const DEFAULT_CONTAINER_REGISTRY_URL: &'static str = "sc2cr.io/applications";
+    pub const DEFAULT_K8S_NAMESPACE: &'static str = "sc2";
+    pub const DEFAULT_CONTAINERD_UNIT: &'static str = "containerd";
+    pub const DEFAULT_SNAPSHOTTER_UNIT: &'static str = "nydus-snapshotter";
+    pub const DEFAULT_SNAPSHOTTER_ROOT: &'static str = "/var/lib/containerd-nydus";
     pub const SYS_NAME: &'static str = "sc2-exp";
 
+    // Git commit (with a -dirty suffix if the tree had local changes) that
+    // we were built from
+    pub const GIT_VERSION: &'static str = env!("SC2_EXP_GIT_VERSION");
+    pub const VERSION: &'static str = concat!(
+        env!("CARGO_PKG_VERSION"),
+        " (",
+        env!("SC2_EXP_GIT_VERSION"),
+        ")"
+    );
+
+    /// Read the settings from a TOML config file (if given), with each
+    /// setting overridable by its SC2_<SETTING> env. var (e.g.
+    /// SC2_CONTAINER_REGISTRY_URL). Settings that are set nowhere keep
+    /// their defaults
+    pub fn from_config(path: Option<&Path>) -> EnvConfig {
+        let mut config = match path {
+            Some(path) => {
+                let contents = fs::read_to_string(path).unwrap_or_else(|e| {
+                    panic!(
+                        "{}(env): failed to read config file at {}: {e}",
+                        Self::SYS_NAME,
+                        path.display()
+                    )
+                });
+                toml::from_str(&contents).unwrap_or_else(|e| {
+                    panic!(
+                        "{}(env): failed to parse config file at {}: {e}",
+                        Self::SYS_NAME,
+                        path.display()
+                    )
+                })
+            }
+            None => EnvConfig::default(),
+        };
+
+        if let Ok(url) = env::var("SC2_CONTAINER_REGISTRY_URL") {
+            config.container_registry_url = url;
+        }
+        if let Ok(namespace) = env::var("SC2_K8S_NAMESPACE") {
+            config.k8s_namespace = namespace;
+        }
+        if let Ok(unit) = env::var("SC2_CONTAINERD_UNIT") {
+            config.containerd_unit = unit;
+        }
+        if let Ok(unit) = env::var("SC2_SNAPSHOTTER_UNIT") {
+            config.snapshotter_unit = unit;
+        }
+        if let Ok(snapshotter_root) = env::var("SC2_SNAPSHOTTER_ROOT") {
+            config.snapshotter_root = PathBuf::from(snapshotter_root);
+        }
+        if let Ok(results_root) = env::var("SC2_RESULTS_ROOT") {
+            config.results_root = Some(PathBuf::from(results_root));
+        }
+        if let Ok(apps_root) = env::var("SC2_APPS_ROOT") {
+            config.apps_root = Some(PathBuf::from(apps_root));
+        }
+
+        config
+    }
+
+    /// Override the default settings. This must be called before any other
+    /// method that reads them, otherwise we stick to the defaults
+    pub fn configure(config: EnvConfig) {
+        ENV_CONFIG
+            .set(config)
+            .expect("sc2-exp(env): settings already initialised");
+    }
+
+    fn get_config() -> &'static EnvConfig {
+        ENV_CONFIG.get_or_init(|| Self::from_config(None))
+    }
+
+    /// Container registry (and path) that we pull the applications from
+    pub fn container_registry_url() -> &'static str {
+        &Self::get_config().container_registry_url
+    }
+
+    /// Namespace that we deploy the experiments to, unless overriden with
+    /// --namespace
+    pub fn k8s_namespace() -> &'static str {
+        &Self::get_config().k8s_namespace
+    }
+
+    /// Systemd unit that containerd runs as, whose logs we parse the
+    /// start-up events from
+    pub fn containerd_unit() -> &'static str {
+        &Self::get_config().containerd_unit
+    }
+
+    /// Systemd unit that the nydus-snapshotter runs as, whose logs we parse
+    /// the snapshotter events from
+    pub fn snapshotter_unit() -> &'static str {
+        &Self::get_config().snapshotter_unit
+    }
+
+    /// Directory where the nydus-snapshotter keeps its data (e.g. the blobs
+    /// that it fetches), on the nodes that we run on
+    pub fn snapshotter_root() -> &'static Path {
+        &Self::get_config().snapshotter_root
+    }
+
     pub fn proj_root() -> PathBuf {
         env::current_dir().expect("sc2-exp(env): failed to get current directory")
     }
 
+    /// Directory that we write results to (and plot them from). Unless
+    /// overriden, this is the results directory in the source tree
     pub fn results_root() -> PathBuf {
+        if let Some(path) = &Self::get_config().results_root {
+            return path.clone();
+        }
+
         let mut path = Self::proj_root();
         path.push("results");
         path
     }
 
+    /// Directory with the applications that we deploy. Unless overriden,
+    /// this is a checkout of the applications repo next to the source tree
     pub fn apps_root() -> PathBuf {
+        if let Some(path) = &Self::get_config().apps_root {
+            return path.clone();
+        }
+
         let mut path = Self::proj_root();
         path.push("..");
         path.push("applications");
@@ -1,70 +1,82 @@
-use crate::{containerd::Containerd, cri::Cri, env::Env, kubernetes::K8s};
-use chrono::{DateTime, Duration, Utc};
+use crate::{
+    containerd::{Containerd, ContainerdEvent, EventTimestamps},
+    cri::{ContainerRuntime, Cri, CriBackend, Crictl},
+    env::Env,
+    kubernetes::K8s,
+    netem::Netem,
+    notify::{Notify, NotifyFormat},
+};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
 use clap::{Args, ValueEnum};
-use indicatif::{ProgressBar, ProgressStyle};
-use log::debug;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use log::{debug, info, warn};
 use plotters::prelude::RGBColor;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde_json::json;
 use std::{
-    collections::BTreeMap, fmt, fs, io::Write, path::PathBuf, process::Command, str, str::FromStr,
+    collections::{BTreeMap, BTreeSet},
+    env, fmt, fs,
+    io::{BufRead, BufReader, Write},
+    panic,
+    path::{Path, PathBuf},
+    process::{self, Command, Output, Stdio},
+    str,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex, OnceLock,
+    },
     thread, time,
 };
 
-#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, ValueEnum)]
-pub enum AvailableBaselines {
-    Runc,
-    Kata,
-    Snp,
-    SnpSc2,
-    Tdx,
-    TdxSc2,
-}
-
-impl fmt::Display for AvailableBaselines {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            AvailableBaselines::Runc => write!(f, "runc"),
-            AvailableBaselines::Kata => write!(f, "kata"),
-            AvailableBaselines::Snp => write!(f, "snp"),
-            AvailableBaselines::SnpSc2 => write!(f, "snp-sc2"),
-            AvailableBaselines::Tdx => write!(f, "tdx"),
-            AvailableBaselines::TdxSc2 => write!(f, "tdx-sc2"),
-        }
+named_enum! {
+    #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, ValueEnum)]
+    pub enum AvailableBaselines {
+        Runc => "runc",
+        Kata => "kata",
+        Gvisor => "gvisor",
+        Snp => "snp",
+        SnpSc2 => "snp-sc2",
+        Tdx => "tdx",
+        TdxSc2 => "tdx-sc2",
     }
 }
 
-impl FromStr for AvailableBaselines {
-    type Err = ();
+impl AvailableBaselines {
+    /// Whether the baseline is an SC2-optimised variant of another baseline
+    pub fn is_sc2(&self) -> bool {
+        matches!(
+            self,
+            AvailableBaselines::SnpSc2 | AvailableBaselines::TdxSc2
+        )
+    }
 
-    fn from_str(input: &str) -> Result<AvailableBaselines, Self::Err> {
-        match input {
-            "runc" => Ok(AvailableBaselines::Runc),
-            "kata" => Ok(AvailableBaselines::Kata),
-            "snp" => Ok(AvailableBaselines::Snp),
-            "snp-sc2" => Ok(AvailableBaselines::SnpSc2),
-            "tdx" => Ok(AvailableBaselines::Tdx),
-            "tdx-sc2" => Ok(AvailableBaselines::TdxSc2),
-            _ => Err(()),
+    /// SC2-optimised variant of the baseline, if it has one
+    pub fn sc2_variant(&self) -> Option<Self> {
+        match self {
+            AvailableBaselines::Snp => Some(AvailableBaselines::SnpSc2),
+            AvailableBaselines::Tdx => Some(AvailableBaselines::TdxSc2),
+            _ => None,
         }
     }
-}
 
-impl AvailableBaselines {
-    pub fn iter_variants() -> std::slice::Iter<'static, AvailableBaselines> {
-        static VARIANTS: [AvailableBaselines; 6] = [
-            AvailableBaselines::Runc,
-            AvailableBaselines::Kata,
-            AvailableBaselines::Snp,
-            AvailableBaselines::SnpSc2,
-            AvailableBaselines::Tdx,
-            AvailableBaselines::TdxSc2,
-        ];
-        VARIANTS.iter()
+    /// Name of the Kubernetes runtime class that we deploy the baseline with
+    pub fn runtime_class_name(&self) -> &'static str {
+        match self {
+            AvailableBaselines::Runc => "runc",
+            AvailableBaselines::Kata => "kata-qemu",
+            AvailableBaselines::Gvisor => "gvisor",
+            AvailableBaselines::Snp => "kata-qemu-snp",
+            AvailableBaselines::SnpSc2 => "kata-qemu-snp-sc2",
+            AvailableBaselines::Tdx => "kata-qemu-tdx",
+            AvailableBaselines::TdxSc2 => "kata-qemu-tdx-sc2",
+        }
     }
 
     pub fn get_color(&self) -> RGBColor {
         match self {
             AvailableBaselines::Runc => RGBColor(122, 92, 117),
             AvailableBaselines::Kata => RGBColor(171, 222, 230),
+            AvailableBaselines::Gvisor => RGBColor(247, 190, 130),
             AvailableBaselines::Snp => RGBColor(203, 170, 203),
             AvailableBaselines::SnpSc2 => RGBColor(213, 160, 163),
             AvailableBaselines::Tdx => RGBColor(255, 255, 181),
@@ -73,6 +85,33 @@ impl AvailableBaselines {
     }
 }
 
+#[derive(Clone, Debug, ValueEnum)]
+pub enum OutputFormat {
+    Csv,
+    Json,
+    Both,
+}
+
+impl OutputFormat {
+    fn has_csv(&self) -> bool {
+        matches!(self, OutputFormat::Csv | OutputFormat::Both)
+    }
+
+    fn has_json(&self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Both)
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Csv => write!(f, "csv"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Both => write!(f, "both"),
+        }
+    }
+}
+
 #[derive(Debug, Args)]
 pub struct ExpRunArgs {
     #[arg(long, num_args = 1.., value_name = "BASELINE")]
@@ -81,21 +120,170 @@ pub struct ExpRunArgs {
     num_repeats: u32,
     #[arg(long, default_value = "1")]
     num_warmup_repeats: u32,
+    #[arg(long, default_value = "0")]
+    num_cold_warmup_repeats: u32,
+    #[arg(long)]
+    record_warmup: bool,
     #[arg(long, default_value = "4")]
     scale_up_range: u32,
+    #[arg(long, num_args = 1.., value_name = "DELAY_MS", default_values_t = [0, 50, 100, 200])]
+    registry_delay_ms: Vec<u32>,
+    #[arg(long, num_args = 1.., value_name = "N", default_values_t = [1, 4, 16])]
+    concurrency: Vec<u32>,
+    #[arg(long, default_value = "30", value_name = "SECS")]
+    duration_secs: u64,
+    #[arg(long, default_value = "1")]
+    min_scale: u32,
+    #[arg(long, value_name = "APP", default_value = "helloworld-py")]
+    app: String,
+    #[arg(long, value_name = "SERVICE_NAME")]
+    use_existing_service: Option<String>,
+    #[arg(long, num_args = 1.., value_name = "EVENT=SECS", value_parser = parse_event_timeout)]
+    event_timeout: Vec<(ContainerdEvent, u64)>,
+    #[arg(long, value_enum, default_value_t = ContainerRuntime::Crictl)]
+    container_runtime: ContainerRuntime,
+    #[arg(long, value_name = "ENDPOINT", default_value = Crictl::DEFAULT_RUNTIME_ENDPOINT)]
+    cri_runtime_endpoint: String,
+    #[arg(long, value_name = "FILE")]
+    payload: Option<PathBuf>,
+    #[arg(long, value_name = "FILE")]
+    warmup_payload: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Csv)]
+    output_format: OutputFormat,
+    #[arg(long)]
+    append: bool,
+    #[arg(long)]
+    dry_run: bool,
+    #[arg(long)]
+    strict_events: bool,
+    #[arg(long, default_value = "3")]
+    curl_max_retries: u32,
+    #[arg(long, default_value = "2", value_name = "SECS")]
+    settle_secs: u64,
+    #[arg(long, value_name = "SECS")]
+    cooldown_secs: Option<u64>,
+    #[arg(long, value_name = "SECS")]
+    run_timeout_secs: Option<u64>,
+    #[arg(long, value_name = "SECS")]
+    pod_ready_timeout_secs: Option<u64>,
+    #[arg(long)]
+    randomize_order: bool,
+    #[arg(long, requires = "randomize_order")]
+    seed: Option<u64>,
+    #[arg(long, value_name = "URL")]
+    notify_webhook: Option<String>,
+    #[arg(long, value_enum, default_value_t = NotifyFormat::Json, requires = "notify_webhook")]
+    notify_format: NotifyFormat,
+    #[arg(long, num_args = 1.., value_name = "BASELINE=NODE", value_parser = parse_node_selector)]
+    node_selector: Vec<(AvailableBaselines, String)>,
+}
+
+/// Progress of a full execution of an experiment (across all baselines and
+/// experiment args), that we report when notifying about its outcome
+#[derive(Debug, Default)]
+struct SweepSummary {
+    num_configs_done: u32,
+    num_failed_runs: u32,
+    num_discarded_runs: u32,
+}
+
+impl SweepSummary {
+    fn record_config(&mut self, outcome: &ConfigOutcome) {
+        self.num_configs_done += 1;
+        self.num_failed_runs += outcome.num_failed_runs;
+        self.num_discarded_runs += outcome.num_discarded_runs;
+    }
+}
+
+/// Runs that we did not record for a single configuration
+#[derive(Debug, Default)]
+struct ConfigOutcome {
+    // Runs that failed (or timed-out)
+    num_failed_runs: u32,
+    // Runs that completed, but whose events did not add up
+    num_discarded_runs: u32,
+}
+
+/// Changes to the cluster that we must undo if we are interrupted (with
+/// Ctrl-C) half-way through an experiment, so that they do not poison the
+/// next run
+#[derive(Debug)]
+struct InFlightState {
+    // YAML path and env. vars of each Knative service that we have deployed
+    // (indexed by service name), as we may run baselines in parallel
+    services: BTreeMap<String, (PathBuf, BTreeMap<String, String>)>,
+    registry_delay: bool,
+}
+
+static IN_FLIGHT_STATE: Mutex<InFlightState> = Mutex::new(InFlightState {
+    services: BTreeMap::new(),
+    registry_delay: false,
+});
+
+/// Whether we have started wrapping up the experiment (i.e. undoing the
+/// in-flight state and notifying about the outcome). Both the Ctrl-C handler
+/// and the main thread (e.g. unwinding from a panic) may try to, so only the
+/// first one to get here does
+static WRAPPING_UP: AtomicBool = AtomicBool::new(false);
+
+/// Progress bars for all the (possibly parallel) runs of the experiment,
+/// so that they do not draw over each other
+static PROGRESS_BARS: OnceLock<MultiProgress> = OnceLock::new();
+
+/// Parse a per-event soft timeout in the form EVENT=SECS
+fn parse_event_timeout(input: &str) -> Result<(ContainerdEvent, u64), String> {
+    let (event, timeout_secs) = input
+        .split_once('=')
+        .ok_or(format!("invalid EVENT=SECS: no '=' found in '{input}'"))?;
+
+    let event: ContainerdEvent = event
+        .parse()
+        .ok()
+        .filter(|event| {
+            ContainerdEvent::iter_variants()
+                .chain(ContainerdEvent::iter_snapshotter_variants())
+                .any(|variant| variant == event)
+        })
+        .ok_or(format!("unrecognised event: {event}"))?;
+
+    let timeout_secs: u64 = timeout_secs
+        .parse()
+        .map_err(|e| format!("invalid timeout '{timeout_secs}': {e}"))?;
+
+    Ok((event, timeout_secs))
+}
+
+/// Parse the node to pin a baseline to, in the form BASELINE=NODE
+fn parse_node_selector(input: &str) -> Result<(AvailableBaselines, String), String> {
+    let (baseline, node) = input
+        .split_once('=')
+        .ok_or(format!("invalid BASELINE=NODE: no '=' found in '{input}'"))?;
+
+    let baseline: AvailableBaselines = baseline
+        .parse()
+        .map_err(|_| format!("unrecognised baseline: {baseline}"))?;
+    if node.is_empty() {
+        return Err(format!("empty node name for baseline: {baseline}"));
+    }
+
+    Ok((baseline, node.to_string()))
 }
 
 #[derive(PartialEq)]
 pub enum AvailableExperiments {
+    RegistryRtt,
     ScaleOut,
     StartUp,
+    Throughput,
 }
 
 impl fmt::Display for AvailableExperiments {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            AvailableExperiments::RegistryRtt => write!(f, "registry-rtt"),
             AvailableExperiments::ScaleOut => write!(f, "scale-out"),
             AvailableExperiments::StartUp => write!(f, "start-up"),
+            AvailableExperiments::Throughput => write!(f, "throughput"),
         }
     }
 }
@@ -106,7 +294,18 @@ pub struct ExecutionResult {
     start_time: DateTime<Utc>,
     end_time: DateTime<Utc>,
     // Breakdown of (start, end) timestamp pairs
-    event_ts: BTreeMap<String, (DateTime<Utc>, DateTime<Utc>)>,
+    event_ts: EventTimestamps,
+    // Per-run metrics that are not time intervals (e.g. resource usage)
+    metrics: BTreeMap<String, u64>,
+    // Per-run free-form information (e.g. flags for slow events)
+    metadata: BTreeMap<String, String>,
+    // Latency of each (successful) request, for the throughput experiment
+    request_times_ms: Vec<u64>,
+    // Time to scale out to each number of replicas, for the scale-out
+    // experiment
+    scale_out_times_ms: BTreeMap<u32, u64>,
+    // Whether this is a (recorded) warm-up run
+    is_warmup: bool,
 }
 
 impl ExecutionResult {
@@ -116,6 +315,11 @@ impl ExecutionResult {
             start_time: Utc::now(),
             end_time: Utc::now(),
             event_ts: BTreeMap::new(),
+            metrics: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            request_times_ms: Vec::new(),
+            scale_out_times_ms: BTreeMap::new(),
+            is_warmup: false,
         }
     }
 }
@@ -130,82 +334,434 @@ impl Default for ExecutionResult {
 pub struct Exp {}
 
 impl Exp {
-    /// Helper functions
-    fn init_data_file(results_file: &PathBuf, exp: &AvailableExperiments) {
-        // Open data file
-        let mut file = fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(results_file)
-            .expect("sc2-eval(k8s): failed to open data file at: {results_file:?}");
+    // Helper functions
 
-        match exp {
-            AvailableExperiments::ScaleOut => {
-                writeln!(file, "Run,TimeMs")
-                    .expect("sc2-eval(k8s): failed to write to data file at: {results_file:?}");
+    /// Initialise the data files for a run of the experiment, and return the
+    /// index of the first run that we will record. Unless told to append to
+    /// existing data files, we always start from a clean slate
+    fn init_data_file(
+        results_file: &Path,
+        exp: &AvailableExperiments,
+        output_format: &OutputFormat,
+        append: bool,
+    ) -> u32 {
+        // Do not mix results from previous executions in different formats
+        if !append {
+            for (data_file, enabled) in [
+                (results_file.to_path_buf(), output_format.has_csv()),
+                (
+                    results_file.with_extension("jsonl"),
+                    output_format.has_json(),
+                ),
+            ] {
+                if !enabled && data_file.exists() {
+                    fs::remove_file(&data_file).unwrap_or_else(|e| {
+                        panic!(
+                            "{}(exp): failed to remove stale data file at: {data_file:?}: {e}",
+                            Env::SYS_NAME
+                        )
+                    });
+                }
             }
-            AvailableExperiments::StartUp => {
-                writeln!(file, "Run,Event,TimeMs")
-                    .expect("sc2-eval(k8s): failed to write to data file at: {results_file:?}");
+        }
+
+        // When appending, carry on from the last run in the existing data
+        let first_run = if append {
+            Self::get_next_run_idx(results_file)
+        } else {
+            0
+        };
+
+        let data_header = match exp {
+            AvailableExperiments::ScaleOut => "Run,ScaleIdx,TimeMs,Phase,StartTime",
+            AvailableExperiments::RegistryRtt | AvailableExperiments::StartUp => {
+                "Run,Event,TimeMs,Phase,StartTime"
             }
+            AvailableExperiments::Throughput => "Run,Request,TimeMs,Phase,StartTime",
+        };
+        let mut files_and_headers = vec![];
+        if output_format.has_csv() {
+            files_and_headers.push((results_file.to_path_buf(), Some(data_header)));
+        }
+        if output_format.has_json() {
+            files_and_headers.push((results_file.with_extension("jsonl"), None));
+        }
+        files_and_headers.push((
+            Self::get_sidecar_file(results_file, "metrics"),
+            Some("Run,Metric,Value"),
+        ));
+        files_and_headers.push((
+            Self::get_sidecar_file(results_file, "metadata"),
+            Some("Run,Key,Value"),
+        ));
+
+        for (file_path, header) in files_and_headers {
+            // Only append to files with the same columns that we write, as
+            // the plots can not read files with rows of different lengths
+            if append && file_path.exists() {
+                if let Some(header) = header {
+                    let mut existing_header = String::new();
+                    fs::File::open(&file_path)
+                        .map(BufReader::new)
+                        .and_then(|mut reader| reader.read_line(&mut existing_header))
+                        .unwrap_or_else(|e| {
+                            panic!(
+                                "{}(exp): failed to read data file at: {file_path:?}: {e}",
+                                Env::SYS_NAME
+                            )
+                        });
+                    let existing_header = existing_header.trim_end();
+                    if existing_header != header {
+                        panic!(
+                            "{}(exp): can not append to {} as its header ({existing_header}) does not match the expected one ({header}), move it out of the way or re-run without --append",
+                            Env::SYS_NAME,
+                            file_path.display()
+                        );
+                    }
+                }
+                continue;
+            }
+
+            let mut file = fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&file_path)
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "{}(exp): failed to open data file at: {file_path:?}: {e}",
+                        Env::SYS_NAME
+                    )
+                });
+            if let Some(header) = header {
+                writeln!(file, "{header}").unwrap_or_else(|e| {
+                    panic!(
+                        "{}(exp): failed to write to data file at: {file_path:?}: {e}",
+                        Env::SYS_NAME
+                    )
+                });
+            }
+        }
+
+        first_run
+    }
+
+    /// Get the index that follows the highest run index in the existing data
+    /// files (in any format) for a run of the experiment
+    fn get_next_run_idx(results_file: &Path) -> u32 {
+        let mut run_idxs: Vec<u32> = Vec::new();
+
+        if let Ok(contents) = fs::read_to_string(results_file) {
+            run_idxs.extend(
+                contents
+                    .lines()
+                    .skip(1)
+                    .filter_map(|line| line.split(',').next()?.parse::<u32>().ok()),
+            );
         }
+
+        if let Ok(contents) = fs::read_to_string(results_file.with_extension("jsonl")) {
+            run_idxs.extend(contents.lines().filter_map(|line| {
+                let record: serde_json::Value = serde_json::from_str(line).ok()?;
+                u32::try_from(record.get("run")?.as_u64()?).ok()
+            }));
+        }
+
+        run_idxs.into_iter().max().map_or(0, |run_idx| run_idx + 1)
     }
 
     fn write_results_to_file(
         results_file: &PathBuf,
         exp: &AvailableExperiments,
+        env_vars: &BTreeMap<&str, String>,
+        output_format: &OutputFormat,
         exec_results: &ExecutionResult,
     ) {
-        let mut file = fs::OpenOptions::new()
-            .read(true)
-            .append(true)
-            .open(results_file)
-            .expect("sc2-eval(k8s): failed to open data file at: {results_file:?}");
+        // Collect the (event, duration) pairs that we record for this run.
+        // The scale-out experiment records one (number of replicas, duration)
+        // pair for each scale-out step, and the throughput experiment one
+        // (request, duration) pair for each request instead
+        let total_duration: Duration = exec_results.end_time - exec_results.start_time;
+        let row_key = match exp {
+            AvailableExperiments::ScaleOut => "scale_idx",
+            AvailableExperiments::Throughput => "request",
+            AvailableExperiments::RegistryRtt | AvailableExperiments::StartUp => "event",
+        };
+        let phase = if exec_results.is_warmup {
+            "warmup"
+        } else {
+            "measured"
+        };
+        // Also record when the run started (in UTC), to correlate slow runs
+        // with other events in the cluster
+        let start_time = exec_results
+            .start_time
+            .to_rfc3339_opts(SecondsFormat::Millis, true);
+        let rows: Vec<(String, i64)> = match exp {
+            AvailableExperiments::ScaleOut => exec_results
+                .scale_out_times_ms
+                .iter()
+                .map(|(scale_idx, time_ms)| (format!("{scale_idx}"), *time_ms as i64))
+                .collect(),
+            AvailableExperiments::Throughput => (0..)
+                .zip(&exec_results.request_times_ms)
+                .map(|(request, time_ms)| (format!("{request}"), *time_ms as i64))
+                .collect(),
+            AvailableExperiments::RegistryRtt | AvailableExperiments::StartUp => {
+                // Manually write-down the end-to-end event, and then all the
+                // events that we decide to record for the break-down of the
+                // start-up time
+                let mut rows = vec![(
+                    format!("{}", ContainerdEvent::StartUp),
+                    total_duration.num_milliseconds(),
+                )];
+                for (event, (start_ts, end_ts)) in &exec_results.event_ts {
+                    let duration: Duration = *end_ts - *start_ts;
+                    rows.push((format!("{event}"), duration.num_milliseconds()));
+                }
+                rows
+            }
+        };
 
-        match exp {
-            AvailableExperiments::ScaleOut => {
-                let duration: Duration = exec_results.end_time - exec_results.start_time;
+        if output_format.has_csv() {
+            let mut file = fs::OpenOptions::new()
+                .read(true)
+                .append(true)
+                .open(results_file)
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "{}(exp): failed to open data file at: {results_file:?}: {e}",
+                        Env::SYS_NAME
+                    )
+                });
+
+            for (event, time_ms) in &rows {
                 writeln!(
                     file,
-                    "{},{}",
-                    exec_results.iter,
-                    duration.num_milliseconds()
+                    "{},{},{},{},{}",
+                    exec_results.iter, event, time_ms, phase, start_time
                 )
-                .expect("sc2-eval(k8s): failed to write to data file at: {results_file:?}");
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "{}(exp): failed to write to data file at: {results_file:?}: {e}",
+                        Env::SYS_NAME
+                    )
+                });
             }
-            AvailableExperiments::StartUp => {
-                // Manually write-down the end-to-end event
-                let total_duration: Duration = exec_results.end_time - exec_results.start_time;
-                writeln!(
-                    file,
-                    "{},StartUp,{}",
-                    exec_results.iter,
-                    total_duration.num_milliseconds()
+        }
+
+        // In JSON Lines format, each record also carries the information
+        // about the run that, in CSV, we only encode in the file name
+        if output_format.has_json() {
+            let json_file = results_file.with_extension("jsonl");
+            let mut file = fs::OpenOptions::new()
+                .read(true)
+                .append(true)
+                .open(&json_file)
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "{}(exp): failed to open data file at: {json_file:?}: {e}",
+                        Env::SYS_NAME
+                    )
+                });
+
+            for (event, time_ms) in &rows {
+                let mut record = serde_json::Map::new();
+                record.insert("run".to_string(), exec_results.iter.into());
+                record.insert(row_key.to_string(), event.clone().into());
+                record.insert("time_ms".to_string(), (*time_ms).into());
+                record.insert("phase".to_string(), phase.into());
+                record.insert("start_time".to_string(), start_time.clone().into());
+                for (key, env_var) in [
+                    ("baseline", "SC2_BASELINE"),
+                    ("workload", "KSERVICE_NAME"),
+                    ("flavour", "START_UP_FLAVOUR"),
+                    ("registry_delay_ms", "REGISTRY_DELAY_MS"),
+                    ("max_scale", "MAX_SCALE"),
+                    ("concurrency", "CONCURRENCY"),
+                ] {
+                    if let Some(value) = env_vars.get(env_var) {
+                        record.insert(key.to_string(), value.clone().into());
+                    }
+                }
+
+                writeln!(file, "{}", serde_json::Value::Object(record)).unwrap_or_else(|e| {
+                    panic!(
+                        "{}(exp): failed to write to data file at: {json_file:?}: {e}",
+                        Env::SYS_NAME
+                    )
+                });
+            }
+        }
+
+        let metrics_file = Self::get_sidecar_file(results_file, "metrics");
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(&metrics_file)
+            .unwrap_or_else(|e| {
+                panic!(
+                    "{}(exp): failed to open metrics file at: {metrics_file:?}: {e}",
+                    Env::SYS_NAME
+                )
+            });
+        for (metric, value) in &exec_results.metrics {
+            writeln!(file, "{},{},{}", exec_results.iter, metric, value).unwrap_or_else(|e| {
+                panic!(
+                    "{}(exp): failed to write to metrics file at: {metrics_file:?}: {e}",
+                    Env::SYS_NAME
                 )
-                .expect("sc2-eval(k8s): failed to write to data file at: {results_file:?}");
+            });
+        }
 
-                // Write all the events that we decide to record for the
-                // break-down of the start-up time
-                for (event, (start_ts, end_ts)) in &exec_results.event_ts {
-                    let duration: Duration = *end_ts - *start_ts;
-                    writeln!(
-                        file,
-                        "{},{},{}",
-                        exec_results.iter,
-                        event,
+        let metadata_file = Self::get_sidecar_file(results_file, "metadata");
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(&metadata_file)
+            .unwrap_or_else(|e| {
+                panic!(
+                    "{}(exp): failed to open metadata file at: {metadata_file:?}: {e}",
+                    Env::SYS_NAME
+                )
+            });
+        for (key, value) in &exec_results.metadata {
+            writeln!(file, "{},{},{}", exec_results.iter, key, value).unwrap_or_else(|e| {
+                panic!(
+                    "{}(exp): failed to write to metadata file at: {metadata_file:?}: {e}",
+                    Env::SYS_NAME
+                )
+            });
+        }
+    }
+
+    /// Per-run information that is not part of the time breakdown is written
+    /// to a file with the same name as the data file, but in a sibling
+    /// directory (e.g. `metrics` or `metadata`), so that it does not get
+    /// mixed with the time breakdowns when plotting
+    fn get_sidecar_file(results_file: &Path, kind: &str) -> PathBuf {
+        let mut sidecar_file: PathBuf = results_file
+            .parent()
+            .and_then(|p| p.parent())
+            .expect("sc2-exp(exp): failed to get results directory")
+            .to_path_buf();
+        sidecar_file.push(kind);
+        fs::create_dir_all(sidecar_file.clone()).unwrap();
+        sidecar_file.push(results_file.file_name().unwrap());
+        sidecar_file
+    }
+
+    /// Check that the sequential events in a run add up to, at most, its
+    /// end-to-end time. Otherwise, the orchestration time that we derive when
+    /// plotting (i.e. StartUp minus all the other events) is negative, which
+    /// means that we mis-parsed the logs
+    fn has_consistent_events(exec_result: &ExecutionResult) -> bool {
+        let start_up_ms = (exec_result.end_time - exec_result.start_time).num_milliseconds();
+        let events_ms: i64 = exec_result
+            .event_ts
+            .iter()
+            .filter(|(event, _)| event.is_sequential())
+            .map(|(_, (start_ts, end_ts))| (*end_ts - *start_ts).num_milliseconds())
+            .sum();
+
+        if events_ms > start_up_ms {
+            warn!(
+                "{}(exp): discarding run {}: its events add up to more than its start-up time ({events_ms} ms > {start_up_ms} ms)",
+                Env::SYS_NAME,
+                exec_result.iter
+            );
+            return false;
+        }
+
+        true
+    }
+
+    /// Check that a cold run actually started from scratch, i.e. that it
+    /// created a new sandbox and pulled the image. Otherwise, it found a pod
+    /// (or the image) left behind by a previous run, and is not a cold start
+    fn has_cold_start_events(exec_result: &ExecutionResult) -> bool {
+        for event in [ContainerdEvent::RunPodSandbox, ContainerdEvent::PullImage] {
+            if !exec_result.event_ts.contains_key(&event) {
+                warn!(
+                    "{}(exp): discarding cold run {}: it has no {event} event",
+                    Env::SYS_NAME,
+                    exec_result.iter
+                );
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Flag the events in a run that took longer than their (soft) timeout.
+    /// We do not fail the run, but record which event was slow, and for how
+    /// long, as run metadata (e.g. `slow:PullImage`)
+    fn flag_slow_events(
+        exec_result: &mut ExecutionResult,
+        event_timeouts: &[(ContainerdEvent, u64)],
+    ) {
+        for (event, timeout_secs) in event_timeouts {
+            let duration = if *event == ContainerdEvent::StartUp {
+                Some(exec_result.end_time - exec_result.start_time)
+            } else {
+                exec_result
+                    .event_ts
+                    .get(event)
+                    .map(|(start_ts, end_ts)| *end_ts - *start_ts)
+            };
+
+            if let Some(duration) = duration {
+                if duration.num_milliseconds() > (*timeout_secs * 1000) as i64 {
+                    warn!(
+                        "{}(exp): run {} exceeded timeout for {event} ({} ms > {timeout_secs} s)",
+                        Env::SYS_NAME,
+                        exec_result.iter,
                         duration.num_milliseconds()
-                    )
-                    .expect("sc2-eval(k8s): failed to write to data file at: {results_file:?}");
+                    );
+                    exec_result.metadata.insert(
+                        format!("slow:{event}"),
+                        duration.num_milliseconds().to_string(),
+                    );
                 }
             }
-        };
+        }
+    }
+
+    /// Check that we can create, and write to, the results directory for the
+    /// experiment. We do this before deploying anything so that we fail fast
+    /// rather than after having run (and lost) the first experiment
+    fn check_results_dir_is_writable(exp: &AvailableExperiments) {
+        let mut results_dir: PathBuf = Env::results_root();
+        results_dir.push(format!("{exp}"));
+        results_dir.push("data");
+
+        if let Err(e) = fs::create_dir_all(&results_dir) {
+            panic!(
+                "{}(exp): failed to create results directory at {}: {e}",
+                Env::SYS_NAME,
+                results_dir.display()
+            );
+        }
+
+        let mut probe_file = results_dir.clone();
+        probe_file.push(format!(".{}-write-check", Env::SYS_NAME));
+        if let Err(e) = fs::write(&probe_file, b"") {
+            panic!(
+                "{}(exp): results directory at {} is not writable: {e}",
+                Env::SYS_NAME,
+                results_dir.display()
+            );
+        }
+        fs::remove_file(&probe_file).unwrap();
     }
 
     /// Helper function to get a progress bar to visualize experiment progress
     fn get_progress_bar(num_repeats: u64, msg: String) -> ProgressBar {
-        let pb = ProgressBar::new(num_repeats);
+        let pb = PROGRESS_BARS
+            .get_or_init(MultiProgress::new)
+            .add(ProgressBar::new(num_repeats));
         pb.set_style(
             ProgressStyle::default_bar()
                 .template("{msg} [{bar:40.cyan/blue}] {pos}/{len} ({percent}%)")
@@ -218,26 +774,120 @@ impl Exp {
 
     /// This method executes a single instance of the experiment by `curl`-ing
     /// the corresponding `service_ip`, and populates the ExecutionResult with
-    /// all the fields required by the `AvailableExperiment` we are running
+    /// all the fields required by the `AvailableExperiment` we are running.
+    /// If given a payload, we POST the file's contents as the request body
     fn run_knative_experiment_once(
         _exp: &AvailableExperiments,
-        service_name: &str,
+        args: &ExpRunArgs,
+        env_vars: &BTreeMap<&str, String>,
+        cri: &dyn CriBackend,
         service_ip: &str,
-    ) -> ExecutionResult {
+        payload: Option<&PathBuf>,
+    ) -> Option<ExecutionResult> {
+        let service_name = &env_vars["KSERVICE_NAME"];
+        let node = env_vars.get("NODE_NAME").map(String::as_str);
+        let curl_max_retries = args.curl_max_retries;
+        let run_timeout = args.run_timeout_secs.map(time::Duration::from_secs);
+        let settle = time::Duration::from_secs(args.settle_secs);
+        let pod_ready_timeout = args.pod_ready_timeout_secs.map(time::Duration::from_secs);
+        let deadline = run_timeout.map(|timeout| time::Instant::now() + timeout);
+
+        // Count the snapshots before the run, to tell whether the run was
+        // served from the snapshotter's cache (i.e. committed no new layers)
+        let snapshotter = if env_vars["SC2_BASELINE"].contains("sc2") {
+            "nydus"
+        } else {
+            "overlayfs"
+        };
+        let num_snapshots_before = Self::get_num_snapshots(snapshotter, node);
+        let cache_size_bytes_before = Self::get_cache_size_bytes(env_vars, cri, node);
+
         // Note that this initialises start_time to Utc::now()
         let mut exec_result = ExecutionResult::new();
 
-        // Do single execution
-        debug!(
-            "{}: running curl command to ip: {service_ip}",
-            Env::SYS_NAME
-        );
-        let output = Command::new("curl")
-            .arg(service_ip)
-            .output()
-            .expect("sc2-eval(k8s): failed to spawn curl command");
+        // Sample containerd's resource usage while the request is in flight.
+        // We sample it from /proc, so we can only do so for the local node
+        let containerd_sampler = match node {
+            Some(_) => None,
+            None => Containerd::start_resource_sampler(),
+        };
+
+        // Do single execution. On a busy cluster, we sometimes get errors
+        // while Knative is still setting up the route, so we retry the
+        // request (with a short back-off) if curl fails or we get a bad
+        // request, and only panic once we run out of retries
+        let mut num_retries = 0;
+        let output = loop {
+            // Only time the last (successful) attempt
+            exec_result.start_time = Utc::now();
+
+            debug!(
+                "{}: running curl command to ip: {service_ip}",
+                Env::SYS_NAME
+            );
+            let mut curl_cmd = Command::new("curl");
+            if let Some(payload) = payload {
+                curl_cmd
+                    .arg("--data-binary")
+                    .arg(format!("@{}", payload.display()));
+            }
+            curl_cmd.arg(service_ip);
+            let Some(output) = Self::run_curl_until(&mut curl_cmd, deadline) else {
+                // The run is stuck, so we give up on it, and scale the
+                // service to zero so that the next run starts afresh
+                if let Some(containerd_sampler) = containerd_sampler {
+                    containerd_sampler.stop();
+                }
+                warn!(
+                    "{}(k8s): run timed out after {}s, scaling service '{service_name}' to zero",
+                    Env::SYS_NAME,
+                    run_timeout.unwrap_or_default().as_secs()
+                );
+                Self::scale_to_zero(service_name, pod_ready_timeout);
+                thread::sleep(settle);
+
+                return None;
+            };
+
+            let is_bad_request = String::from_utf8_lossy(&output.stdout).contains("Bad Request");
+            if (output.status.success() && !is_bad_request) || num_retries >= curl_max_retries {
+                break output;
+            }
+
+            num_retries += 1;
+            warn!(
+                "{}(k8s): curl to {service_ip} failed (retry {num_retries}/{curl_max_retries})",
+                Env::SYS_NAME
+            );
+            thread::sleep(time::Duration::from_secs(num_retries.into()));
+        };
+
+        match containerd_sampler.map(|containerd_sampler| containerd_sampler.stop()) {
+            Some(Some((containerd_cpu_ms, containerd_peak_rss_kb))) => {
+                exec_result
+                    .metrics
+                    .insert("ContainerdCpuMs".to_string(), containerd_cpu_ms);
+                exec_result
+                    .metrics
+                    .insert("ContainerdPeakRssKb".to_string(), containerd_peak_rss_kb);
+            }
+            Some(None) => warn!(
+                "{}(containerd): lost track of containerd while sampling its resource usage, skipping it",
+                Env::SYS_NAME
+            ),
+            None => {}
+        }
 
         match output.status.code() {
+            Some(0) if String::from_utf8_lossy(&output.stdout).contains("Bad Request") => {
+                let stdout =
+                    str::from_utf8(&output.stdout).unwrap_or("sc2-exp(k8s): failed to get stdout");
+                panic!(
+                    "{}(k8s): curl got a bad request after {num_retries} retries: {stdout}\npod logs:\n{}",
+                    Env::SYS_NAME,
+                    K8s::get_pod_logs(service_name)
+                );
+            }
             Some(0) => {
                 exec_result.end_time = Utc::now();
 
@@ -252,17 +902,36 @@ impl Exp {
                 let stderr =
                     str::from_utf8(&output.stderr).unwrap_or("sc2-exp(k8s): failed to get stderr");
                 panic!(
-                    "{}(k8s): kubectl exited with error (code: {code}): stdout: {stdout} - stderr: {stderr}",
-                    Env::SYS_NAME
+                    "{}(k8s): kubectl exited with error (code: {code}): stdout: {stdout} - stderr: {stderr}\npod logs:\n{}",
+                    Env::SYS_NAME,
+                    K8s::get_pod_logs(service_name)
                 );
             }
             None => {
                 let stderr =
                     str::from_utf8(&output.stderr).unwrap_or("sc2-exp(k8s): failed to get stderr");
-                panic!("{}(k8s): kubectl command failed: {stderr}", Env::SYS_NAME);
+                panic!(
+                    "{}(k8s): kubectl command failed: {stderr}\npod logs:\n{}",
+                    Env::SYS_NAME,
+                    K8s::get_pod_logs(service_name)
+                );
             }
         };
 
+        // Read the pods' peak memory usage once we have stopped the clock,
+        // but before we scale them to zero
+        match K8s::get_pods_peak_memory_kb(service_name, node) {
+            Some(peak_memory_kb) => {
+                exec_result
+                    .metrics
+                    .insert("PeakMemoryKb".to_string(), peak_memory_kb);
+            }
+            None => warn!(
+                "{}(k8s): failed to read the peak memory usage of service '{service_name}'",
+                Env::SYS_NAME
+            ),
+        }
+
         let deployment_id = K8s::get_knative_deployment_id(service_name);
         // Get the cutoff time to filter outputs of the journal log, and leave us some slack
         let cutoff_time = exec_result.start_time - chrono::Duration::milliseconds(500);
@@ -271,61 +940,274 @@ impl Exp {
             "{}(k8s): got knative deployment id: {deployment_id}",
             Env::SYS_NAME
         );
-        exec_result.event_ts = Containerd::get_events_from_journalctl(&deployment_id, &cutoff_time);
+        // If we fail to parse the logs (or, with --strict-events, we miss
+        // some events), we drop the run rather than writing bogus timings to
+        // the results
+        let mut event_ts = Containerd::get_events_from_journalctl(
+            &deployment_id,
+            &cutoff_time,
+            node,
+            args.strict_events,
+        );
+
+        // SC2 baselines use the nydus-snapshotter, so we also parse its logs
+        if env_vars["SC2_BASELINE"].contains("sc2") {
+            event_ts = event_ts.and_then(|(mut event_ts, pod_ids)| {
+                event_ts.extend(Containerd::get_snapshotter_events_from_journalctl(
+                    &cutoff_time,
+                    &exec_result.end_time,
+                    node,
+                )?);
+                Ok((event_ts, pod_ids))
+            });
+        }
+
+        match event_ts {
+            Ok((event_ts, pod_ids)) => {
+                exec_result.event_ts = event_ts;
+
+                // Keep the sandbox and container ids, so that we can go back
+                // and inspect a given (e.g. slow) run
+                if let Some(sandbox_id) = pod_ids.sandbox_id {
+                    exec_result
+                        .metadata
+                        .insert("SandboxId".to_string(), sandbox_id);
+                }
+                for (name, container_id) in pod_ids.container_ids {
+                    exec_result
+                        .metadata
+                        .insert(format!("ContainerId:{name}"), container_id);
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "{}(exp): discarding run for '{deployment_id}': {e}",
+                    Env::SYS_NAME
+                );
+                Self::scale_to_zero(service_name, pod_ready_timeout);
+                thread::sleep(settle);
+
+                return None;
+            }
+        }
+
+        // We can only tell if the run hit the cache if we counted the
+        // snapshots both before and after it
+        let num_snapshots_after = Self::get_num_snapshots(snapshotter, node);
+        if let (Some(num_snapshots_before), Some(num_snapshots_after)) =
+            (num_snapshots_before, num_snapshots_after)
+        {
+            let cache_hit = num_snapshots_after <= num_snapshots_before;
+            exec_result
+                .metadata
+                .insert("CacheHit".to_string(), cache_hit.to_string());
+
+            // A warm start that re-creates the snapshot is effectively a cold
+            // start, and would inflate the warm numbers
+            if !cache_hit && env_vars.get("START_UP_FLAVOUR").map(String::as_str) == Some("warm") {
+                warn!(
+                    "{}(exp): warm run committed {} new {snapshotter} snapshot(s)",
+                    Env::SYS_NAME,
+                    num_snapshots_after - num_snapshots_before
+                );
+            }
+        }
+
+        // Also record the size of the image cache before and after the run,
+        // to tell how much of the image each baseline actually materialized
+        for (key, cache_size_bytes) in [
+            ("CacheSizeBytesBefore", cache_size_bytes_before),
+            (
+                "CacheSizeBytesAfter",
+                Self::get_cache_size_bytes(env_vars, cri, node),
+            ),
+        ] {
+            if let Some(cache_size_bytes) = cache_size_bytes {
+                exec_result
+                    .metadata
+                    .insert(key.to_string(), cache_size_bytes.to_string());
+            }
+        }
 
         // Common clean-up after single execution
         debug!(
             "{}(k8s): scaling service '{service_name}' to zero",
             Env::SYS_NAME
         );
-        K8s::scale_knative_service_to_zero(service_name);
+        Self::scale_to_zero(service_name, pod_ready_timeout);
 
         // Cautionary sleep between runs
-        thread::sleep(time::Duration::from_secs(2));
+        thread::sleep(settle);
 
         // Return execution result
-        exec_result
+        Some(exec_result)
     }
 
-    fn clean_up_after_run(exp: &AvailableExperiments, env_vars: &BTreeMap<&str, String>) {
-        if exp == &AvailableExperiments::StartUp && env_vars["START_UP_FLAVOUR"] == "cold" {
-            if env_vars["SC2_BASELINE"].contains("sc2") {
-                Cri::remove_image(format!(
-                    "{}/helloworld-py:unencrypted-nydus",
-                    env_vars["CTR_REGISTRY_URL"]
-                ));
-            } else {
-                Cri::remove_image(format!(
-                    "{}/helloworld-py:unencrypted",
-                    env_vars["CTR_REGISTRY_URL"]
-                ));
+    /// Count the snapshotter's committed snapshots on the node. We only
+    /// warn if we fail to, as the run itself is not affected
+    fn get_num_snapshots(snapshotter: &str, node: Option<&str>) -> Option<usize> {
+        Containerd::count_snapshots(snapshotter, node)
+            .inspect_err(|e| {
+                warn!(
+                    "{}(exp): failed to count the {snapshotter} snapshots, not recording CacheHit: {e}",
+                    Env::SYS_NAME
+                )
+            })
+            .ok()
+    }
+
+    /// Get the size (in bytes) of the node's image cache. The SC2 baselines
+    /// pull images lazily, so, for them, we measure the nydus-snapshotter's
+    /// data directory, rather than the image store. We only warn if we fail
+    /// to, as the run itself is not affected
+    fn get_cache_size_bytes(
+        env_vars: &BTreeMap<&str, String>,
+        cri: &dyn CriBackend,
+        node: Option<&str>,
+    ) -> Option<u64> {
+        let cache_size_bytes = if env_vars["SC2_BASELINE"].contains("sc2") {
+            Containerd::snapshotter_root_size_bytes(node)
+        } else {
+            Cri::image_cache_size_bytes(cri)
+        };
+
+        cache_size_bytes
+            .inspect_err(|e| {
+                warn!(
+                    "{}(exp): failed to get the size of the image cache: {e}",
+                    Env::SYS_NAME
+                )
+            })
+            .ok()
+    }
+
+    /// Scale a service to zero between runs. If the pods do not go away in
+    /// time we only warn, as the run itself is not affected
+    fn scale_to_zero(service_name: &str, timeout: Option<time::Duration>) {
+        if let Err(e) = K8s::scale_knative_service_to_zero(service_name, timeout) {
+            warn!(
+                "{}(k8s): failed to scale service '{service_name}' to zero: {e}",
+                Env::SYS_NAME
+            );
+        }
+    }
+
+    /// Run a curl command, and kill it if it has not finished by the
+    /// deadline (if any). We wait for curl in a separate thread, so that we
+    /// can stop waiting for it when the deadline expires
+    fn run_curl_until(curl_cmd: &mut Command, deadline: Option<time::Instant>) -> Option<Output> {
+        let curl = curl_cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("sc2-eval(k8s): failed to spawn curl command");
+
+        let Some(deadline) = deadline else {
+            return Some(
+                curl.wait_with_output()
+                    .expect("sc2-eval(k8s): failed to wait for curl command"),
+            );
+        };
+
+        let curl_pid = curl.id();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            // The receiver is gone if we timed-out, so ignore send errors
+            let _ = tx.send(curl.wait_with_output());
+        });
+
+        match rx.recv_timeout(deadline.saturating_duration_since(time::Instant::now())) {
+            Ok(output) => Some(output.expect("sc2-eval(k8s): failed to wait for curl command")),
+            Err(_) => {
+                Command::new("kill")
+                    .args(["-9", &curl_pid.to_string()])
+                    .output()
+                    .expect("sc2-eval(k8s): failed to kill curl command");
+                None
             }
         }
     }
 
-    /// This method takes a _single_ deployment configuration, specified as
-    /// a YAML file and a map of env. vars to template it, and executes it
-    /// according to the requested experiment, using the given run args
-    fn run_knative_experiment(
+    /// Whether each run of a configuration must start from an empty image
+    /// store (i.e. pull the image)
+    fn is_cold_start(exp: &AvailableExperiments, env_vars: &BTreeMap<&str, String>) -> bool {
+        match exp {
+            AvailableExperiments::RegistryRtt => true,
+            AvailableExperiments::ScaleOut | AvailableExperiments::Throughput => false,
+            AvailableExperiments::StartUp => env_vars["START_UP_FLAVOUR"] == "cold",
+        }
+    }
+
+    /// Get the number of warm-up runs for a configuration. We do not
+    /// warm-up existing services, and, by default, we do not warm-up cold
+    /// starts either, as each of them starts from an empty image store
+    fn get_num_warmup_repeats(
         exp: &AvailableExperiments,
         args: &ExpRunArgs,
-        yaml_path: &PathBuf,
+        env_vars: &BTreeMap<&str, String>,
+    ) -> u32 {
+        if args.use_existing_service.is_some() {
+            0
+        } else if Self::is_cold_start(exp, env_vars) {
+            args.num_cold_warmup_repeats
+        } else {
+            args.num_warmup_repeats
+        }
+    }
+
+    fn clean_up_after_run(
+        exp: &AvailableExperiments,
+        cri: &dyn CriBackend,
         env_vars: &BTreeMap<&str, String>,
     ) {
-        // Deploy the baseline
-        let service_ip = K8s::deploy_knative_service(yaml_path, env_vars);
+        let is_cold_start = Self::is_cold_start(exp, env_vars);
 
-        // Cautionary sleep before starting the experiment
-        thread::sleep(time::Duration::from_secs(2));
+        let image_tag = if env_vars["SC2_BASELINE"].contains("sc2") {
+            format!(
+                "{}/{}:unencrypted-nydus",
+                env_vars["CTR_REGISTRY_URL"], env_vars["IMAGE_NAME"]
+            )
+        } else {
+            format!(
+                "{}/{}:unencrypted",
+                env_vars["CTR_REGISTRY_URL"], env_vars["IMAGE_NAME"]
+            )
+        };
 
-        // Initialise data file
+        // For cold starts, we remove the image so that the next run must
+        // pull it. For warm starts, we explicitly pull it, so that the next
+        // run does not depend on what previous runs left in the image store
+        let is_warm_start =
+            matches!(exp, AvailableExperiments::StartUp) && env_vars["START_UP_FLAVOUR"] == "warm";
+        if is_cold_start {
+            match Cri::remove_image(cri, image_tag) {
+                Ok(()) => {}
+                Err(e) if e.is_recoverable() => {
+                    warn!("{}(exp): could not remove image: {e}", Env::SYS_NAME)
+                }
+                Err(e) => panic!("{}(exp): failed to remove image: {e}", Env::SYS_NAME),
+            }
+        } else if is_warm_start {
+            if let Err(e) = Cri::pull_image(cri, &image_tag) {
+                warn!("{}(exp): {e}", Env::SYS_NAME);
+            }
+        }
+    }
+
+    /// Get the data file that we write the results of a configuration to
+    fn get_results_file(exp: &AvailableExperiments, env_vars: &BTreeMap<&str, String>) -> PathBuf {
         let mut results_file: PathBuf = Env::results_root();
         results_file.push(format!("{exp}"));
         results_file.push("data");
-        fs::create_dir_all(results_file.clone()).unwrap();
         results_file.push(match &exp {
+            AvailableExperiments::RegistryRtt => {
+                format!(
+                    "{}_{}.csv",
+                    env_vars["SC2_BASELINE"], env_vars["REGISTRY_DELAY_MS"]
+                )
+            }
             AvailableExperiments::ScaleOut => {
-                format!("{}_{}.csv", env_vars["SC2_BASELINE"], env_vars["SCALE_IDX"])
+                format!("{}_{}.csv", env_vars["SC2_BASELINE"], env_vars["MAX_SCALE"])
             }
             AvailableExperiments::StartUp => {
                 format!(
@@ -333,48 +1215,774 @@ impl Exp {
                     env_vars["SC2_BASELINE"], env_vars["START_UP_FLAVOUR"]
                 )
             }
+            AvailableExperiments::Throughput => {
+                format!(
+                    "{}_{}.csv",
+                    env_vars["SC2_BASELINE"], env_vars["CONCURRENCY"]
+                )
+            }
         });
-        Self::init_data_file(&results_file, exp);
 
-        // Run the experiment (warm-up)
-        for _ in 0..args.num_warmup_repeats {
-            Self::run_knative_experiment_once(exp, &env_vars["KSERVICE_NAME"], &service_ip);
-            Self::clean_up_after_run(exp, env_vars);
+        results_file
+    }
+
+    /// Get the env. var that each experiment sweeps over (for each baseline)
+    fn get_sweep_var(exp: &AvailableExperiments) -> &'static str {
+        match exp {
+            AvailableExperiments::RegistryRtt => "REGISTRY_DELAY_MS",
+            AvailableExperiments::ScaleOut => "MAX_SCALE",
+            AvailableExperiments::StartUp => "START_UP_FLAVOUR",
+            AvailableExperiments::Throughput => "CONCURRENCY",
+        }
+    }
+
+    /// Get the values that we sweep over (for each baseline), in order
+    fn get_sweep_values(exp: &AvailableExperiments, args: &ExpRunArgs) -> Vec<String> {
+        match exp {
+            AvailableExperiments::RegistryRtt => args
+                .registry_delay_ms
+                .iter()
+                .map(|delay_ms| delay_ms.to_string())
+                .collect(),
+            // We scale out a single service in each run
+            AvailableExperiments::ScaleOut => vec![args.scale_up_range.to_string()],
+            // An existing service has already been warmed, so we can only
+            // measure the warm flavour against it
+            AvailableExperiments::StartUp => match &args.use_existing_service {
+                Some(_) => vec!["warm".to_string()],
+                None => vec!["cold".to_string(), "warm".to_string()],
+            },
+            AvailableExperiments::Throughput => args
+                .concurrency
+                .iter()
+                .map(|concurrency| concurrency.to_string())
+                .collect(),
+        }
+    }
+
+    /// Get the order in which we run the baselines, and the sweep values
+    /// for each of them. With --randomize-order, we shuffle both (with a
+    /// seeded RNG, so that we can reproduce the order) so that any drift
+    /// over a long sweep does not always penalise the same configurations
+    fn get_run_order(
+        exp: &AvailableExperiments,
+        args: &ExpRunArgs,
+    ) -> (Option<u64>, Vec<AvailableBaselines>, Vec<String>) {
+        let mut baselines = args.baseline.clone();
+        let mut sweep_values = Self::get_sweep_values(exp, args);
+        if !args.randomize_order {
+            return (None, baselines, sweep_values);
+        }
+
+        let seed = args.seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
+        baselines.shuffle(&mut rng);
+        sweep_values.shuffle(&mut rng);
+
+        (Some(seed), baselines, sweep_values)
+    }
+
+    /// Run a command, and return its (trimmed) output, or None if it failed
+    fn get_cmd_output(program: &str, args: &[&str]) -> Option<String> {
+        Command::new(program)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Record everything that we need to trace the results of an experiment
+    /// back to the code and machines that produced them, in a manifest next
+    /// to the experiment's `data` directory. We write one manifest for each
+    /// invocation (named after its start time), so that appending to, or
+    /// re-running part of, an experiment does not lose the previous ones.
+    /// With --randomize-order, we also record the seed, and the order that
+    /// we run the configurations in, so that we can reproduce it with --seed
+    fn write_manifest(
+        exp: &AvailableExperiments,
+        args: &ExpRunArgs,
+        start_time: DateTime<Utc>,
+        run_order: (Option<u64>, &[AvailableBaselines], &[String]),
+    ) {
+        let mut manifest_file = Env::results_root();
+        manifest_file.push(format!("{exp}"));
+        manifest_file.push("manifests");
+        manifest_file.push(format!("{}.json", start_time.format("%Y%m%dT%H%M%SZ")));
+
+        // Get the kernel version of the nodes that we run on. Unless we pin
+        // baselines to nodes, we run on (and read the logs of) this machine
+        let mut kernels = BTreeMap::<String, Option<String>>::new();
+        if args.node_selector.is_empty() {
+            kernels.insert(
+                Self::get_cmd_output("hostname", &[]).unwrap_or("localhost".to_string()),
+                Self::get_cmd_output("uname", &["-r"]),
+            );
+        }
+        for (_, node) in &args.node_selector {
+            kernels.insert(
+                node.clone(),
+                Self::get_cmd_output("ssh", &[node, "uname", "-r"]),
+            );
+        }
+
+        let deploy_sha = env::var("SC2_DEPLOY_SOURCE")
+            .ok()
+            .and_then(|deploy_source| {
+                Self::get_cmd_output("git", &["-C", &deploy_source, "rev-parse", "HEAD"])
+            });
+        let (seed, baselines, sweep_values) = run_order;
+        let run_order = seed.map(|seed| {
+            json!({
+                "seed": seed,
+                "baselines": baselines.iter().map(|baseline| format!("{baseline}")).collect::<Vec<_>>(),
+                Self::get_sweep_var(exp): sweep_values,
+            })
+        });
+        let manifest = json!({
+            "git_sha": Env::GIT_VERSION,
+            "kernel": kernels,
+            "deploy_sha": deploy_sha,
+            "args": env::args().collect::<Vec<_>>(),
+            "timestamp": start_time.to_rfc3339(),
+            "run_order": run_order,
+        });
+        let manifest_dir = manifest_file.parent().unwrap();
+        fs::create_dir_all(manifest_dir).unwrap_or_else(|e| {
+            panic!(
+                "{}(exp): failed to create manifest directory at {}: {e}",
+                Env::SYS_NAME,
+                manifest_dir.display()
+            )
+        });
+        fs::write(
+            &manifest_file,
+            serde_json::to_string_pretty(&manifest).unwrap(),
+        )
+        .unwrap_or_else(|e| {
+            panic!(
+                "{}(exp): failed to write manifest to {}: {e}",
+                Env::SYS_NAME,
+                manifest_file.display()
+            )
+        });
+
+        if let Some(seed) = seed {
+            info!(
+                "{}(exp): running in random order (seed: {seed}), recorded at: {}",
+                Env::SYS_NAME,
+                manifest_file.display()
+            );
+        }
+    }
+
+    /// Print, as a table, every configuration that running the experiment
+    /// with the same args would execute, and the data file that its results
+    /// would go to, without touching the cluster
+    pub fn list(exp: &AvailableExperiments, args: &ExpRunArgs) {
+        let sweep_var = Self::get_sweep_var(exp);
+        let (seed, baselines, sweep_values) = Self::get_run_order(exp, args);
+
+        if let Some(seed) = seed {
+            println!("{}(exp): random order (seed: {seed})", Env::SYS_NAME);
+        }
+        println!(
+            "{:<10} {:<18} {:<9} DATA FILE",
+            "BASELINE", sweep_var, "WARM-UPS"
+        );
+        for baseline in &baselines {
+            for value in &sweep_values {
+                let env_vars = BTreeMap::from([
+                    ("SC2_BASELINE", format!("{baseline}")),
+                    (sweep_var, value.clone()),
+                ]);
+                println!(
+                    "{:<10} {:<18} {:<9} {}",
+                    format!("{baseline}"),
+                    value,
+                    Self::get_num_warmup_repeats(exp, args, &env_vars),
+                    Self::get_results_file(exp, &env_vars).display()
+                );
+            }
+        }
+
+        let num_configs = baselines.len() * sweep_values.len();
+        println!(
+            "{}(exp): {exp}: {num_configs} configurations, {} runs each",
+            Env::SYS_NAME,
+            args.num_repeats,
+        );
+    }
+
+    /// This method takes a _single_ deployment configuration, specified as
+    /// a YAML file and a map of env. vars to template it, and executes it
+    /// according to the requested experiment, using the given run args
+    fn run_knative_experiment(
+        exp: &AvailableExperiments,
+        args: &ExpRunArgs,
+        yaml_path: &PathBuf,
+        env_vars: &BTreeMap<&str, String>,
+    ) -> ConfigOutcome {
+        let results_file = Self::get_results_file(exp, env_vars);
+
+        // In a dry run, only print what we would run
+        if args.dry_run {
+            println!(
+                "{}(exp): [dry-run] {exp}: would deploy {} and write results to {}",
+                Env::SYS_NAME,
+                yaml_path.display(),
+                results_file.display()
+            );
+            for (key, value) in env_vars {
+                println!("{}(exp): [dry-run]     {key}={value}", Env::SYS_NAME);
+            }
+            return ConfigOutcome::default();
+        }
+
+        // Deploy the baseline, unless we are measuring against a service that
+        // someone else has already deployed (and warmed)
+        let pod_ready_timeout = args.pod_ready_timeout_secs.map(time::Duration::from_secs);
+        let service_ip = match &args.use_existing_service {
+            Some(service_name) => K8s::get_knative_service_ip(service_name, pod_ready_timeout),
+            None => {
+                // Track the service before deploying it, so that we also
+                // clean it up if we are interrupted while deploying
+                IN_FLIGHT_STATE.lock().unwrap().services.insert(
+                    env_vars["KSERVICE_NAME"].clone(),
+                    (
+                        yaml_path.clone(),
+                        env_vars
+                            .iter()
+                            .map(|(key, value)| (key.to_string(), value.clone()))
+                            .collect(),
+                    ),
+                );
+                K8s::deploy_knative_service(yaml_path, env_vars, pod_ready_timeout)
+            }
+        };
+
+        // If the service never becomes ready (e.g. bad image or scheduling
+        // failure) skip this configuration, and count all its runs as failed
+        let service_ip = match service_ip {
+            Ok(service_ip) => service_ip,
+            Err(e) => {
+                warn!(
+                    "{}(exp): skipping {exp} for '{}': {e}",
+                    Env::SYS_NAME,
+                    env_vars["KSERVICE_NAME"]
+                );
+                Self::delete_service(args, yaml_path, env_vars);
+
+                return ConfigOutcome {
+                    num_failed_runs: args.num_repeats,
+                    ..Default::default()
+                };
+            }
+        };
+
+        // Cautionary sleep before starting the experiment
+        let settle = time::Duration::from_secs(args.settle_secs);
+        thread::sleep(settle);
+
+        // Initialise data file
+        fs::create_dir_all(results_file.parent().unwrap()).unwrap();
+        let first_run = Self::init_data_file(&results_file, exp, &args.output_format, args.append);
+
+        // The throughput and scale-out experiments load the service, rather
+        // than measuring individual starts
+        let num_failed_runs = match exp {
+            AvailableExperiments::Throughput => Some(Self::run_throughput(
+                args,
+                env_vars,
+                &service_ip,
+                &results_file,
+                first_run,
+            )),
+            AvailableExperiments::ScaleOut => Some(Self::run_scale_out(
+                args,
+                env_vars,
+                &service_ip,
+                &results_file,
+                first_run,
+            )),
+            AvailableExperiments::RegistryRtt | AvailableExperiments::StartUp => None,
+        };
+        if let Some(num_failed_runs) = num_failed_runs {
+            Self::delete_service(args, yaml_path, env_vars);
+
+            return ConfigOutcome {
+                num_failed_runs,
+                ..Default::default()
+            };
+        }
+
+        let cri = args.container_runtime.get_backend(
+            &args.cri_runtime_endpoint,
+            env_vars.get("NODE_NAME").map(String::as_str),
+        );
+
+        // Run the experiment (warm-up). Unless told otherwise, we warm-up
+        // with the same payload that we measure with
+        let is_cold_start = Self::is_cold_start(exp, env_vars);
+        let warmup_payload = args.warmup_payload.as_ref().or(args.payload.as_ref());
+        let num_warmup_repeats = Self::get_num_warmup_repeats(exp, args, env_vars);
+        let mut num_recorded_warmups = 0;
+        for _ in 0..num_warmup_repeats {
+            let exec_results = Self::run_knative_experiment_once(
+                exp,
+                args,
+                env_vars,
+                cri.as_ref(),
+                &service_ip,
+                warmup_payload,
+            );
+            Self::clean_up_after_run(exp, cri.as_ref(), env_vars);
+
+            // Unless asked to, we discard the warm-up runs. Recorded ones
+            // come first, so the measured runs carry on from them
+            if let (true, Some(mut exec_results)) = (args.record_warmup, exec_results) {
+                exec_results.iter = first_run + num_recorded_warmups;
+                exec_results.is_warmup = true;
+                if !Self::has_consistent_events(&exec_results)
+                    || (is_cold_start && !Self::has_cold_start_events(&exec_results))
+                {
+                    continue;
+                }
+                Self::flag_slow_events(&mut exec_results, &args.event_timeout);
+                Self::write_results_to_file(
+                    &results_file,
+                    exp,
+                    env_vars,
+                    &args.output_format,
+                    &exec_results,
+                );
+                num_recorded_warmups += 1;
+            }
+        }
+        let first_run = first_run + num_recorded_warmups;
+
+        // Deploying the service (and any warm-up run) leaves a pod, and the
+        // image, behind. Make sure that the first cold run starts afresh too
+        if is_cold_start {
+            Self::scale_to_zero(&env_vars["KSERVICE_NAME"], pod_ready_timeout);
+            Self::clean_up_after_run(exp, cri.as_ref(), env_vars);
+            thread::sleep(settle);
         }
 
         // Run the actual experiment
         let pb = Self::get_progress_bar(
             args.num_repeats.into(),
             match &exp {
-                AvailableExperiments::ScaleOut => {
+                AvailableExperiments::RegistryRtt => {
                     format!(
-                        "{}/{}/{}",
-                        exp, env_vars["SC2_BASELINE"], env_vars["SCALE_IDX"]
+                        "{}/{}/{}ms",
+                        exp, env_vars["SC2_BASELINE"], env_vars["REGISTRY_DELAY_MS"]
                     )
                 }
-                AvailableExperiments::StartUp => {
+                AvailableExperiments::ScaleOut
+                | AvailableExperiments::StartUp
+                | AvailableExperiments::Throughput => {
                     format!(
                         "{}/{}/{}",
-                        exp, env_vars["SC2_BASELINE"], env_vars["START_UP_FLAVOUR"]
+                        exp,
+                        env_vars["SC2_BASELINE"],
+                        env_vars[Self::get_sweep_var(exp)]
                     )
                 }
             },
         );
+        let mut outcome = ConfigOutcome::default();
         for i in 0..args.num_repeats {
+            if i > 0 {
+                Self::cool_down(args);
+            }
+
             // Run experiment
-            let mut exec_results =
-                Self::run_knative_experiment_once(exp, &env_vars["KSERVICE_NAME"], &service_ip);
-            Self::clean_up_after_run(exp, env_vars);
+            let exec_results = Self::run_knative_experiment_once(
+                exp,
+                args,
+                env_vars,
+                cri.as_ref(),
+                &service_ip,
+                args.payload.as_ref(),
+            );
+            Self::clean_up_after_run(exp, cri.as_ref(), env_vars);
+
+            // Runs that timed-out do not make it to the results file
+            let Some(mut exec_results) = exec_results else {
+                outcome.num_failed_runs += 1;
+                pb.inc(1);
+                continue;
+            };
+
+            // Neither do runs with a mis-parsed break-down, as they would
+            // quietly skew the averages
+            exec_results.iter = first_run + i;
+            if !Self::has_consistent_events(&exec_results)
+                || (is_cold_start && !Self::has_cold_start_events(&exec_results))
+            {
+                outcome.num_discarded_runs += 1;
+                pb.inc(1);
+                continue;
+            }
 
             // Write results to file
-            exec_results.iter = i;
-            Self::write_results_to_file(&results_file, exp, &exec_results);
+            Self::flag_slow_events(&mut exec_results, &args.event_timeout);
+            Self::write_results_to_file(
+                &results_file,
+                exp,
+                env_vars,
+                &args.output_format,
+                &exec_results,
+            );
             pb.inc(1);
         }
         pb.finish();
 
-        // Delete the experiment
-        K8s::delete_knative_service(yaml_path, env_vars);
+        if outcome.num_failed_runs > 0 {
+            warn!(
+                "{}(exp): {}/{} runs failed and were not recorded",
+                Env::SYS_NAME,
+                outcome.num_failed_runs,
+                args.num_repeats
+            );
+        }
+        if outcome.num_discarded_runs > 0 {
+            warn!(
+                "{}(exp): {}/{} runs had inconsistent (or missing) events and were discarded",
+                Env::SYS_NAME,
+                outcome.num_discarded_runs,
+                args.num_repeats
+            );
+        }
+
+        Self::delete_service(args, yaml_path, env_vars);
+
+        outcome
+    }
+
+    /// Idle between measured runs, if asked to, so that each run starts from
+    /// a comparable thermal state (back-to-back runs heat the CPU, and make
+    /// the later runs in a sweep slower)
+    fn cool_down(args: &ExpRunArgs) {
+        if let Some(cooldown_secs) = args.cooldown_secs {
+            debug!("{}(exp): cooling down for {cooldown_secs}s", Env::SYS_NAME);
+            thread::sleep(time::Duration::from_secs(cooldown_secs));
+        }
+    }
+
+    /// Delete the service that we deployed for a configuration, but leave
+    /// external services untouched
+    fn delete_service(args: &ExpRunArgs, yaml_path: &PathBuf, env_vars: &BTreeMap<&str, String>) {
+        if args.use_existing_service.is_none() {
+            K8s::delete_knative_service(yaml_path, env_vars);
+            IN_FLIGHT_STATE
+                .lock()
+                .unwrap()
+                .services
+                .remove(&env_vars["KSERVICE_NAME"]);
+        }
+    }
+
+    /// Run the throughput experiment against a deployed service. We first
+    /// scale the service up (rather than to zero), and then, in each run, fire
+    /// requests at it from many concurrent clients for a fixed duration. We
+    /// record the latency of each request, and the number of requests (and
+    /// failed requests) in each run, from which we get the throughput
+    fn run_throughput(
+        args: &ExpRunArgs,
+        env_vars: &BTreeMap<&str, String>,
+        service_ip: &str,
+        results_file: &PathBuf,
+        first_run: u32,
+    ) -> u32 {
+        let service_name = &env_vars["KSERVICE_NAME"];
+        let concurrency: u32 = env_vars["CONCURRENCY"].parse().unwrap();
+        let pod_ready_timeout = args.pod_ready_timeout_secs.map(time::Duration::from_secs);
+
+        // We leave the scale of existing services as it is
+        if args.use_existing_service.is_none() {
+            if let Err(e) =
+                K8s::scale_knative_service(service_name, args.min_scale, pod_ready_timeout)
+            {
+                warn!(
+                    "{}(exp): skipping throughput for '{service_name}': {e}",
+                    Env::SYS_NAME
+                );
+                return args.num_repeats;
+            }
+        }
+
+        Self::run_repeats(
+            &AvailableExperiments::Throughput,
+            args,
+            env_vars,
+            results_file,
+            first_run,
+            format!(
+                "{}/{}/{concurrency}x",
+                AvailableExperiments::Throughput,
+                env_vars["SC2_BASELINE"]
+            ),
+            || Self::run_throughput_once(args, service_name, service_ip, concurrency),
+        )
+    }
+
+    /// Scale a single service out, one replica at a time, up to
+    /// `--scale-up-range` replicas in each run. Each run starts with the
+    /// service scaled to zero
+    fn run_scale_out(
+        args: &ExpRunArgs,
+        env_vars: &BTreeMap<&str, String>,
+        service_ip: &str,
+        results_file: &PathBuf,
+        first_run: u32,
+    ) -> u32 {
+        let service_name = &env_vars["KSERVICE_NAME"];
+        let max_scale: u32 = env_vars["MAX_SCALE"].parse().unwrap();
+        let pod_ready_timeout = args.pod_ready_timeout_secs.map(time::Duration::from_secs);
+
+        Self::scale_to_zero(service_name, pod_ready_timeout);
+        thread::sleep(time::Duration::from_secs(args.settle_secs));
+
+        Self::run_repeats(
+            &AvailableExperiments::ScaleOut,
+            args,
+            env_vars,
+            results_file,
+            first_run,
+            format!(
+                "{}/{}/{max_scale}",
+                AvailableExperiments::ScaleOut,
+                env_vars["SC2_BASELINE"]
+            ),
+            || Self::run_scale_out_once(args, service_name, service_ip, max_scale),
+        )
+    }
+
+    /// Run the warm-up and measured runs of an experiment that loads an
+    /// already deployed service (rather than measuring individual starts),
+    /// and write down their results. We return the number of failed runs
+    fn run_repeats(
+        exp: &AvailableExperiments,
+        args: &ExpRunArgs,
+        env_vars: &BTreeMap<&str, String>,
+        results_file: &PathBuf,
+        first_run: u32,
+        progress_msg: String,
+        mut run_once: impl FnMut() -> Option<ExecutionResult>,
+    ) -> u32 {
+        let num_warmup_repeats = Self::get_num_warmup_repeats(exp, args, env_vars);
+        let mut num_recorded_warmups = 0;
+        for _ in 0..num_warmup_repeats {
+            let exec_results = run_once();
+
+            // Unless asked to, we discard the warm-up runs
+            if let (true, Some(mut exec_results)) = (args.record_warmup, exec_results) {
+                exec_results.iter = first_run + num_recorded_warmups;
+                exec_results.is_warmup = true;
+                Self::write_results_to_file(
+                    results_file,
+                    exp,
+                    env_vars,
+                    &args.output_format,
+                    &exec_results,
+                );
+                num_recorded_warmups += 1;
+            }
+        }
+        let first_run = first_run + num_recorded_warmups;
+
+        let pb = Self::get_progress_bar(args.num_repeats.into(), progress_msg);
+        let mut num_failed_runs = 0;
+        for i in 0..args.num_repeats {
+            if i > 0 {
+                Self::cool_down(args);
+            }
+
+            let Some(mut exec_results) = run_once() else {
+                num_failed_runs += 1;
+                pb.inc(1);
+                continue;
+            };
+
+            exec_results.iter = first_run + i;
+            Self::write_results_to_file(
+                results_file,
+                exp,
+                env_vars,
+                &args.output_format,
+                &exec_results,
+            );
+            pb.inc(1);
+        }
+        pb.finish();
+
+        num_failed_runs
+    }
+
+    /// Scale a service out from zero to `max_scale` replicas, one replica at
+    /// a time. To get to N replicas, we fire requests at the service from N
+    /// concurrent clients (which, with a target concurrency of one, makes
+    /// Knative's autoscaler add a replica), and time until all N replicas
+    /// are ready. We scale the service back to zero afterwards
+    fn run_scale_out_once(
+        args: &ExpRunArgs,
+        service_name: &str,
+        service_ip: &str,
+        max_scale: u32,
+    ) -> Option<ExecutionResult> {
+        let settle = time::Duration::from_secs(args.settle_secs);
+        let pod_ready_timeout = args.pod_ready_timeout_secs.map(time::Duration::from_secs);
+        let label = format!("apps.sc2.io/name={service_name}");
+
+        let mut exec_results = ExecutionResult::new();
+        for scale_idx in 1..=max_scale {
+            let start = time::Instant::now();
+            let is_done = AtomicBool::new(false);
+            let result = thread::scope(|scope| {
+                let clients = scope.spawn(|| {
+                    Self::fire_concurrent_requests(args, service_ip, scale_idx, &|| {
+                        is_done.load(Ordering::Relaxed)
+                    })
+                });
+                let result = K8s::wait_for_ready_pods(
+                    K8s::namespace(),
+                    &label,
+                    scale_idx as usize,
+                    pod_ready_timeout,
+                );
+                is_done.store(true, Ordering::Relaxed);
+                clients.join().unwrap();
+
+                result
+            });
+
+            if let Err(e) = result {
+                warn!(
+                    "{}(exp): failed to scale '{service_name}' out to {scale_idx} replicas: {e}",
+                    Env::SYS_NAME
+                );
+                Self::scale_to_zero(service_name, pod_ready_timeout);
+                thread::sleep(settle);
+
+                return None;
+            }
+
+            exec_results
+                .scale_out_times_ms
+                .insert(scale_idx, start.elapsed().as_millis() as u64);
+        }
+        exec_results.end_time = Utc::now();
+
+        Self::scale_to_zero(service_name, pod_ready_timeout);
+        thread::sleep(settle);
+
+        Some(exec_results)
+    }
+
+    /// Fire requests at a (warm) service from a number of concurrent clients
+    /// for the duration of a run, and record the latency of each request, and
+    /// the number of requests (and failed requests) in the run. A run where
+    /// all requests failed tells us nothing about the throughput, so we
+    /// return None instead
+    fn run_throughput_once(
+        args: &ExpRunArgs,
+        service_name: &str,
+        service_ip: &str,
+        concurrency: u32,
+    ) -> Option<ExecutionResult> {
+        let mut exec_results = ExecutionResult::new();
+        let run_end = time::Instant::now() + time::Duration::from_secs(args.duration_secs);
+        let request_times_ms =
+            Self::fire_concurrent_requests(args, service_ip, concurrency, &|| {
+                time::Instant::now() >= run_end
+            });
+        exec_results.end_time = Utc::now();
+
+        let num_requests = request_times_ms.len() as u64;
+        exec_results.request_times_ms = request_times_ms.into_iter().flatten().collect();
+        let num_failed_requests = num_requests - exec_results.request_times_ms.len() as u64;
+        exec_results
+            .metrics
+            .insert("NumRequests".to_string(), num_requests);
+        exec_results
+            .metrics
+            .insert("NumFailedRequests".to_string(), num_failed_requests);
+        exec_results.metrics.insert(
+            "DurationMs".to_string(),
+            (exec_results.end_time - exec_results.start_time).num_milliseconds() as u64,
+        );
+
+        if exec_results.request_times_ms.is_empty() {
+            return None;
+        }
+        if num_failed_requests > 0 {
+            warn!(
+                "{}(exp): {num_failed_requests}/{num_requests} requests to '{service_name}' failed",
+                Env::SYS_NAME
+            );
+        }
+
+        Some(exec_results)
+    }
+
+    /// Fire requests at a service from a number of concurrent clients until
+    /// `is_done` holds. Each client sends a request as soon as the previous
+    /// one returns. We return the latency (in ms) of each request, or None
+    /// if it failed (or, with --run-timeout-secs, timed out)
+    fn fire_concurrent_requests(
+        args: &ExpRunArgs,
+        service_ip: &str,
+        concurrency: u32,
+        is_done: &(dyn Fn() -> bool + Sync),
+    ) -> Vec<Option<u64>> {
+        let payload = args.payload.as_ref().map(|payload| {
+            fs::read(payload).unwrap_or_else(|e| {
+                panic!(
+                    "{}(exp): failed to read payload '{}': {e}",
+                    Env::SYS_NAME,
+                    payload.display()
+                )
+            })
+        });
+
+        thread::scope(|scope| {
+            let clients: Vec<_> = (0..concurrency)
+                .map(|_| {
+                    scope.spawn(|| {
+                        // Each client re-uses its connection across requests,
+                        // so that we do not measure the connection set-up
+                        let mut agent = ureq::AgentBuilder::new();
+                        if let Some(secs) = args.run_timeout_secs {
+                            agent = agent.timeout(time::Duration::from_secs(secs));
+                        }
+                        let agent = agent.build();
+
+                        let mut request_times_ms = Vec::new();
+                        while !is_done() {
+                            let start = time::Instant::now();
+                            let response = match &payload {
+                                Some(payload) => agent.post(service_ip).send_bytes(payload),
+                                None => agent.get(service_ip).call(),
+                            };
+                            let is_ok = response
+                                .ok()
+                                .and_then(|response| response.into_string().ok())
+                                .is_some_and(|body| !body.contains("Bad Request"));
+                            request_times_ms
+                                .push(is_ok.then(|| start.elapsed().as_millis() as u64));
+                        }
+                        request_times_ms
+                    })
+                })
+                .collect();
+
+            clients
+                .into_iter()
+                .flat_map(|client| client.join().unwrap())
+                .collect()
+        })
     }
 
     /// Main entrypoint to execute an experiment in SC2. We iterate over the
@@ -384,71 +1992,397 @@ impl Exp {
     /// we can call run_knative_experiment to handle the deployment, execution,
     /// clean-up, and result aggregation
     pub fn run(exp: &AvailableExperiments, args: &ExpRunArgs) {
+        let start_time = time::Instant::now();
+        let summary = Arc::new(Mutex::new(SweepSummary::default()));
+
+        // If interrupted, undo any changes to the cluster that would poison
+        // the next run, and report how far we got before exiting
+        let exp_name = format!("{exp}");
+        let notify_webhook = args.notify_webhook.clone();
+        let notify_format = args.notify_format.clone();
+        let handler_summary = Arc::clone(&summary);
+        ctrlc::set_handler(move || {
+            // The main thread is already wrapping up, and will exit once done
+            if WRAPPING_UP.swap(true, Ordering::SeqCst) {
+                return;
+            }
+
+            warn!(
+                "{}(exp): interrupted, cleaning-up before exiting",
+                Env::SYS_NAME
+            );
+            Self::undo_in_flight_state();
+
+            if let Some(url) = &notify_webhook {
+                Self::notify_outcome(
+                    url,
+                    &notify_format,
+                    &exp_name,
+                    &handler_summary.lock().unwrap(),
+                    start_time,
+                    "interrupted",
+                    None,
+                );
+            }
+
+            process::exit(130);
+        })
+        .expect("sc2-exp(exp): failed to set Ctrl-C handler");
+
+        // Catch any fatal error, so that we can also notify about it before
+        // carrying on with the panic
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            Self::run_sweep(exp, args, &summary)
+        }));
+
+        // If the Ctrl-C handler got here first, let it wrap up (and exit)
+        if WRAPPING_UP.swap(true, Ordering::SeqCst) {
+            loop {
+                thread::park();
+            }
+        }
+
+        // After a fatal error, we may be half-way through a configuration,
+        // so undo its changes to the cluster too
+        if result.is_err() {
+            Self::undo_in_flight_state();
+        }
+
+        let num_discarded_runs = summary.lock().unwrap().num_discarded_runs;
+        if num_discarded_runs > 0 {
+            warn!(
+                "{}(exp): discarded {num_discarded_runs} runs in total whose events did not add up to their start-up time",
+                Env::SYS_NAME
+            );
+        }
+
+        if let Some(url) = &args.notify_webhook {
+            let error = result.as_ref().err().map(|err| {
+                err.downcast_ref::<String>()
+                    .map(String::as_str)
+                    .or(err.downcast_ref::<&str>().copied())
+                    .unwrap_or("unknown error")
+            });
+            Self::notify_outcome(
+                url,
+                &args.notify_format,
+                &format!("{exp}"),
+                &summary.lock().unwrap(),
+                start_time,
+                if result.is_ok() { "success" } else { "failure" },
+                error,
+            );
+        }
+
+        if let Err(err) = result {
+            panic::resume_unwind(err);
+        }
+    }
+
+    /// Post a summary of the outcome of a run to a webhook
+    fn notify_outcome(
+        url: &str,
+        format: &NotifyFormat,
+        exp_name: &str,
+        summary: &SweepSummary,
+        start_time: time::Instant,
+        status: &str,
+        error: Option<&str>,
+    ) {
+        let mut results_dir = Env::results_root();
+        results_dir.push(exp_name);
+
+        let mut notification = json!({
+            "experiment": exp_name,
+            "status": status,
+            "configurations_completed": summary.num_configs_done,
+            "failed_runs": summary.num_failed_runs,
+            "discarded_runs": summary.num_discarded_runs,
+            "total_time_secs": start_time.elapsed().as_secs(),
+            "results_path": results_dir.display().to_string(),
+        });
+        if let Some(error) = error {
+            notification["error"] = error.into();
+        }
+
+        Notify::post_webhook(url, format, &notification);
+    }
+
+    /// Undo the changes to the cluster that we are in the middle of (i.e. a
+    /// deployed service, or an injected registry delay)
+    fn undo_in_flight_state() {
+        let mut in_flight = IN_FLIGHT_STATE.lock().unwrap();
+
+        for (yaml_path, env_vars) in std::mem::take(&mut in_flight.services).into_values() {
+            debug!(
+                "{}(exp): deleting service at: {}",
+                Env::SYS_NAME,
+                yaml_path.display()
+            );
+            let env_vars: BTreeMap<&str, String> = env_vars
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.clone()))
+                .collect();
+            K8s::delete_knative_service(&yaml_path, &env_vars);
+        }
+
+        if in_flight.registry_delay {
+            debug!("{}(exp): removing registry delay", Env::SYS_NAME);
+            Netem::remove_registry_delay();
+            in_flight.registry_delay = false;
+        }
+    }
+
+    /// Run the experiment for every baseline and experiment arg, keeping
+    /// track of our progress in the summary
+    fn run_sweep(exp: &AvailableExperiments, args: &ExpRunArgs, summary: &Mutex<SweepSummary>) {
+        if !args.dry_run {
+            Self::check_results_dir_is_writable(exp);
+        }
+
+        // Fail early if the request payloads do not exist
+        for payload in [&args.payload, &args.warmup_payload].into_iter().flatten() {
+            if !payload.is_file() {
+                panic!(
+                    "{}(exp): payload file does not exist: {}",
+                    Env::SYS_NAME,
+                    payload.display()
+                );
+            }
+        }
+
+        // Fail early if the application to deploy does not exist
+        if *exp != AvailableExperiments::ScaleOut {
+            let mut app_dir = Env::apps_root();
+            app_dir.push("functions");
+            app_dir.push(&args.app);
+            if !app_dir.is_dir() {
+                panic!(
+                    "{}(exp): application does not exist: {}",
+                    Env::SYS_NAME,
+                    app_dir.display()
+                );
+            }
+        }
+
+        // Fail early if the cluster does not support some of the baselines
+        // (e.g. tdx on an SNP host), as their pods would never be scheduled.
+        // Note that we deploy runc without a runtime class
+        if !args.dry_run {
+            let runtime_classes = K8s::get_runtime_classes();
+            let unavailable_baselines: Vec<String> = args
+                .baseline
+                .iter()
+                .filter(|baseline| **baseline != AvailableBaselines::Runc)
+                .filter(|baseline| !runtime_classes.contains_key(baseline.runtime_class_name()))
+                .map(|baseline| {
+                    format!(
+                        "{baseline} (runtime class: {})",
+                        baseline.runtime_class_name()
+                    )
+                })
+                .collect();
+            if !unavailable_baselines.is_empty() {
+                panic!(
+                    "{}(exp): baselines not available in the cluster: {}",
+                    Env::SYS_NAME,
+                    unavailable_baselines.join(", ")
+                );
+            }
+
+            // A runtime class may only be available in some nodes (i.e. the
+            // ones with the labels in its node selector). Check that the
+            // node that we pin the baseline to, or, otherwise, any node, has
+            // them
+            let node_labels = K8s::get_node_labels();
+            let unschedulable_baselines: Vec<String> = args
+                .baseline
+                .iter()
+                .filter(|baseline| **baseline != AvailableBaselines::Runc)
+                .filter_map(|baseline| {
+                    let node_selector = &runtime_classes[baseline.runtime_class_name()];
+                    let pinned_node = args
+                        .node_selector
+                        .iter()
+                        .find(|(pinned_baseline, _)| pinned_baseline == baseline)
+                        .map(|(_, node)| node);
+                    let is_schedulable = node_labels
+                        .iter()
+                        .filter(|(node, _)| pinned_node.is_none_or(|pinned| pinned == *node))
+                        .any(|(_, labels)| {
+                            node_selector
+                                .iter()
+                                .all(|(key, value)| labels.get(key) == Some(value))
+                        });
+
+                    (!is_schedulable).then(|| {
+                        format!(
+                            "{baseline} (runtime class: {}, node selector: {node_selector:?}, node: {})",
+                            baseline.runtime_class_name(),
+                            pinned_node.map(String::as_str).unwrap_or("any")
+                        )
+                    })
+                })
+                .collect();
+            if !unschedulable_baselines.is_empty() {
+                panic!(
+                    "{}(exp): no node matches the runtime class of baselines: {}",
+                    Env::SYS_NAME,
+                    unschedulable_baselines.join(", ")
+                );
+            }
+        }
+
+        let (seed, baselines, sweep_values) = Self::get_run_order(exp, args);
+        if !args.dry_run {
+            Self::write_manifest(exp, args, Utc::now(), (seed, &baselines, &sweep_values));
+        }
+
+        // Optionally, run each baseline in parallel, pinned to its own node
+        let nodes: BTreeMap<&AvailableBaselines, &str> = args
+            .node_selector
+            .iter()
+            .map(|(baseline, node)| (baseline, node.as_str()))
+            .collect();
+        if nodes.is_empty() {
+            for baseline in &baselines {
+                Self::run_baseline(exp, args, baseline, &sweep_values, None, summary);
+            }
+            return;
+        }
+
+        if *exp != AvailableExperiments::StartUp || args.use_existing_service.is_some() {
+            panic!(
+                "{}(exp): --node-selector is only supported for the start-up experiment, without --use-existing-service",
+                Env::SYS_NAME
+            );
+        }
         for baseline in &args.baseline {
-            // Work-out the Knative service to deploy
-            let mut apps_root = Env::apps_root();
-
-            let yaml_path: PathBuf = match &exp {
-                AvailableExperiments::ScaleOut => {
-                    apps_root.push("functions");
-                    apps_root.push("helloworld-py-scaleout");
-                    apps_root.push("service.yaml");
-                    apps_root
+            if !nodes.contains_key(baseline) {
+                panic!(
+                    "{}(exp): no node to run baseline on (missing --node-selector {baseline}=<node>)",
+                    Env::SYS_NAME
+                );
+            }
+        }
+        if nodes.values().collect::<BTreeSet<_>>().len() != nodes.len() {
+            panic!(
+                "{}(exp): baselines must run on different nodes, not to interfere with each other",
+                Env::SYS_NAME
+            );
+        }
+
+        thread::scope(|scope| {
+            for baseline in &baselines {
+                let node = nodes[baseline];
+                let sweep_values = &sweep_values;
+                scope.spawn(move || {
+                    Self::run_baseline(exp, args, baseline, sweep_values, Some(node), summary)
+                });
+            }
+        });
+    }
+
+    /// Run the experiment for a single baseline, and all experiment args (i.e.
+    /// the given sweep values, in order). If given a node, we pin the service
+    /// to it, and name the service after the baseline, so that we can run
+    /// different baselines in parallel
+    fn run_baseline(
+        exp: &AvailableExperiments,
+        args: &ExpRunArgs,
+        baseline: &AvailableBaselines,
+        sweep_values: &[String],
+        node: Option<&str>,
+        summary: &Mutex<SweepSummary>,
+    ) {
+        // Work-out the Knative service to deploy
+        let mut apps_root = Env::apps_root();
+
+        let yaml_path: PathBuf = match &exp {
+            AvailableExperiments::ScaleOut => {
+                apps_root.push("functions");
+                apps_root.push("helloworld-py-scaleout");
+                apps_root.push("service.yaml");
+                apps_root
+            }
+            AvailableExperiments::RegistryRtt
+            | AvailableExperiments::StartUp
+            | AvailableExperiments::Throughput => {
+                apps_root.push("functions");
+                if baseline.is_sc2() {
+                    apps_root.push(format!("{}-nydus", args.app));
+                } else {
+                    apps_root.push(&args.app);
                 }
-                AvailableExperiments::StartUp => match &baseline {
-                    AvailableBaselines::Runc
-                    | AvailableBaselines::Kata
-                    | AvailableBaselines::Snp
-                    | AvailableBaselines::Tdx => {
-                        apps_root.push("functions");
-                        apps_root.push("helloworld-py");
-                        apps_root.push("service.yaml");
-                        apps_root
-                    }
-                    AvailableBaselines::SnpSc2 | AvailableBaselines::TdxSc2 => {
-                        apps_root.push("functions");
-                        apps_root.push("helloworld-py-nydus");
-                        apps_root.push("service.yaml");
-                        apps_root
-                    }
-                },
-            };
+                apps_root.push("service.yaml");
+                apps_root
+            }
+        };
 
-            // Work-out the env. vars that we need to template in the service file
-            let mut env_vars: BTreeMap<&str, String> = BTreeMap::from([
-                ("SC2_BASELINE", format!("{baseline}")),
-                ("SC2_NAMESPACE", Env::K8S_NAMESPACE.to_string()),
-                ("CTR_REGISTRY_URL", Env::CONTAINER_REGISTRY_URL.to_string()),
-                (
-                    "RUNTIME_CLASS_NAME",
-                    match baseline {
-                        AvailableBaselines::Runc => "runc".to_string(),
-                        AvailableBaselines::Kata => "kata-qemu".to_string(),
-                        AvailableBaselines::Snp => "kata-qemu-snp".to_string(),
-                        AvailableBaselines::SnpSc2 => "kata-qemu-snp-sc2".to_string(),
-                        AvailableBaselines::Tdx => "kata-qemu-tdx".to_string(),
-                        AvailableBaselines::TdxSc2 => "kata-qemu-tdx-sc2".to_string(),
-                    },
-                ),
-            ]);
+        // Work-out the env. vars that we need to template in the service file
+        let mut env_vars: BTreeMap<&str, String> = BTreeMap::from([
+            ("SC2_BASELINE", format!("{baseline}")),
+            ("SC2_NAMESPACE", K8s::namespace().to_string()),
+            (
+                "CTR_REGISTRY_URL",
+                Env::container_registry_url().to_string(),
+            ),
+            (
+                "RUNTIME_CLASS_NAME",
+                baseline.runtime_class_name().to_string(),
+            ),
+        ]);
 
-            // Per-experiment env. var templating and execution
-            match &exp {
-                AvailableExperiments::ScaleOut => {
-                    env_vars.insert("KSERVICE_NAME", "helloworld-py".to_string());
-                    for i in 1..args.scale_up_range {
-                        env_vars.insert("SCALE_IDX", i.to_string());
-                        Self::run_knative_experiment(exp, args, &yaml_path, &env_vars);
+        // Per-experiment env. var templating and execution
+        let kservice_name = args.use_existing_service.clone().unwrap_or(match &exp {
+            AvailableExperiments::ScaleOut => "helloworld-py".to_string(),
+            _ => args.app.clone(),
+        });
+        let kservice_name = match node {
+            Some(node) => {
+                env_vars.insert("NODE_NAME", node.to_string());
+                format!("{kservice_name}-{baseline}")
+            }
+            None => kservice_name,
+        };
+        match &exp {
+            AvailableExperiments::RegistryRtt => {
+                env_vars.insert("KSERVICE_NAME", kservice_name.clone());
+                env_vars.insert("IMAGE_NAME", args.app.clone());
+                for delay_ms in sweep_values {
+                    env_vars.insert("REGISTRY_DELAY_MS", delay_ms.clone());
+                    let delay_ms: u32 = delay_ms.parse().unwrap();
+
+                    // Only inject the delay for the duration of the run
+                    if delay_ms > 0 && !args.dry_run {
+                        Netem::add_registry_delay(delay_ms);
+                        IN_FLIGHT_STATE.lock().unwrap().registry_delay = true;
                     }
-                }
-                AvailableExperiments::StartUp => {
-                    env_vars.insert("KSERVICE_NAME", "helloworld-py".to_string());
-                    for flavour in ["cold", "warm"] {
-                        env_vars.insert("START_UP_FLAVOUR", flavour.to_string());
-                        Self::run_knative_experiment(exp, args, &yaml_path, &env_vars);
+                    let outcome = Self::run_knative_experiment(exp, args, &yaml_path, &env_vars);
+                    summary.lock().unwrap().record_config(&outcome);
+                    if delay_ms > 0 && !args.dry_run {
+                        Netem::remove_registry_delay();
+                        IN_FLIGHT_STATE.lock().unwrap().registry_delay = false;
                     }
                 }
-            };
-        }
+            }
+            AvailableExperiments::ScaleOut
+            | AvailableExperiments::StartUp
+            | AvailableExperiments::Throughput => {
+                env_vars.insert("KSERVICE_NAME", kservice_name.clone());
+                if *exp == AvailableExperiments::ScaleOut {
+                    // We deploy the service with a single replica, and scale
+                    // it out from there
+                    env_vars.insert("SCALE_IDX", "1".to_string());
+                } else {
+                    env_vars.insert("IMAGE_NAME", args.app.clone());
+                }
+                for value in sweep_values {
+                    env_vars.insert(Self::get_sweep_var(exp), value.clone());
+                    let outcome = Self::run_knative_experiment(exp, args, &yaml_path, &env_vars);
+                    summary.lock().unwrap().record_config(&outcome);
+                }
+            }
+        };
     }
 }
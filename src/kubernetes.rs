@@ -1,52 +1,373 @@
 use crate::env::Env;
-use log::debug;
+use futures::StreamExt;
+use k8s_openapi::api::{
+    core::v1::{Node, Pod},
+    node::v1::RuntimeClass,
+};
+use kube::{
+    api::{Api, ApiResource, DynamicObject, GroupVersionKind, ListParams, LogParams},
+    runtime::{
+        reflector,
+        wait::{self, await_condition},
+        watcher, WatchStreamExt,
+    },
+    Client,
+};
+use log::{debug, warn};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use serde_norway::{Mapping, Value};
 use std::{
     collections::BTreeMap,
-    env, fs,
+    env, fmt, fs,
     io::Write,
     path::PathBuf,
-    process::{Command, Stdio},
-    str, thread, time,
+    process::{Command, Output, Stdio},
+    str,
+    sync::{Arc, OnceLock},
+    thread, time,
 };
+use tokio::runtime::Runtime;
+
+/// Kubernetes API client, and the (async) runtime that drives it, that we
+/// share across all calls, and threads
+static KUBE_CLIENT: OnceLock<(Runtime, Client)> = OnceLock::new();
+
+/// Cluster settings that we may override from the command line
+#[derive(Debug)]
+struct K8sConfig {
+    namespace: String,
+    kubectl_path: Option<PathBuf>,
+    kubectl_max_retries: u32,
+}
+
+static K8S_CONFIG: OnceLock<K8sConfig> = OnceLock::new();
+
+/// Errors waiting for Kubernetes resources to reach a given state
+#[derive(Debug)]
+pub enum K8sError {
+    // We gave up waiting after the deadline, with the last status we saw
+    Timeout {
+        waiting_for: String,
+        timeout: time::Duration,
+        last_status: String,
+    },
+    Wait(wait::Error),
+}
+
+impl fmt::Display for K8sError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            K8sError::Timeout {
+                waiting_for,
+                timeout,
+                last_status,
+            } => write!(
+                f,
+                "timed out after {}s waiting for {waiting_for} (last status: {last_status})",
+                timeout.as_secs()
+            ),
+            K8sError::Wait(e) => write!(f, "failed to wait for resource: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for K8sError {}
 
 #[derive(Debug)]
 pub struct K8s {}
 
 impl K8s {
-    fn get_kubectl_cmd() -> String {
-        // For the moment, we literally run the `kubectl` command installed
-        // as part of `coco-serverless`. We may change this in the future
-        match env::var("SC2_DEPLOY_SOURCE") {
-            Ok(value) => format!("{value}/bin/kubectl"),
-            Err(_) => panic!("invrs(eval): failed to read SC2_DEPLOY_SOURCE env. var"),
-        }
+    const CGROUP_ROOT: &'static str = "/sys/fs/cgroup";
+    const POD_LOGS_TAIL_LINES: i64 = 50;
+    pub const DEFAULT_KUBECTL_MAX_RETRIES: u32 = 3;
+    const KUBECTL_INITIAL_BACKOFF_MS: u64 = 500;
+
+    // Errors from the API server that are usually transient on a loaded
+    // cluster, and that we retry kubectl commands on (lower-case)
+    const KUBECTL_TRANSIENT_ERRORS: [&'static str; 8] = [
+        "connection refused",
+        "connection reset by peer",
+        "etcdserver: request timed out",
+        "too many requests",
+        "i/o timeout",
+        "tls handshake timeout",
+        "the server is currently unable to handle the request",
+        "unable to connect to the server",
+    ];
+
+    /// Override the namespace that we deploy to, and the kubectl binary that
+    /// we use. This must be called before any other K8s method, otherwise we
+    /// stick to the defaults
+    pub fn configure(namespace: &str, kubectl_path: Option<PathBuf>, kubectl_max_retries: u32) {
+        K8S_CONFIG
+            .set(K8sConfig {
+                namespace: namespace.to_string(),
+                kubectl_path,
+                kubectl_max_retries,
+            })
+            .expect("sc2-exp(k8s): kubernetes settings already initialised");
     }
 
-    pub fn run_kubectl_cmd(cmd: &str) -> String {
-        debug!("{}(k8s): running kubectl command: {cmd}", Env::SYS_NAME);
-        let args: Vec<&str> = cmd.split_whitespace().collect();
+    fn get_config() -> &'static K8sConfig {
+        K8S_CONFIG.get_or_init(|| K8sConfig {
+            namespace: Env::k8s_namespace().to_string(),
+            kubectl_path: None,
+            kubectl_max_retries: Self::DEFAULT_KUBECTL_MAX_RETRIES,
+        })
+    }
 
-        let output = Command::new(Self::get_kubectl_cmd())
-            .args(&args[0..])
-            .output()
-            .expect("sc2-eval(k8s): failed to spawn kubectl command");
+    /// Namespace that we deploy the experiments to
+    pub fn namespace() -> &'static str {
+        &Self::get_config().namespace
+    }
+
+    fn get_kubectl_cmd() -> PathBuf {
+        if let Some(kubectl_path) = &Self::get_config().kubectl_path {
+            return kubectl_path.clone();
+        }
 
-        match output.status.code() {
-            Some(0) => {}
-            Some(code) => {
-                let stderr =
-                    str::from_utf8(&output.stderr).unwrap_or("sc2-exp(k8s): failed to get stderr");
+        // By default, we run the `kubectl` command installed as part of
+        // `coco-serverless`, and fall-back to the one in the PATH
+        if let Ok(value) = env::var("SC2_DEPLOY_SOURCE") {
+            return PathBuf::from(format!("{value}/bin/kubectl"));
+        }
+
+        env::var_os("PATH")
+            .and_then(|paths| {
+                env::split_paths(&paths)
+                    .map(|dir| dir.join("kubectl"))
+                    .find(|path| path.is_file())
+            })
+            .unwrap_or_else(|| {
                 panic!(
-                    "{}(k8s): kubectl exited with error (code: {code}): {stderr}",
+                    "{}(k8s): failed to find kubectl (set --kubectl-path or SC2_DEPLOY_SOURCE)",
                     Env::SYS_NAME
-                );
+                )
+            })
+    }
+
+    /// Get the Kubernetes API client, initialising it (from the default
+    /// kubeconfig) on first use
+    fn get_kube_client() -> &'static (Runtime, Client) {
+        KUBE_CLIENT.get_or_init(|| {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .enable_all()
+                .build()
+                .expect("sc2-exp(k8s): failed to start async runtime");
+            let client = runtime.block_on(Client::try_default()).unwrap_or_else(|e| {
+                panic!(
+                    "{}(k8s): failed to create kubernetes client: {e}",
+                    Env::SYS_NAME
+                )
+            });
+
+            (runtime, client)
+        })
+    }
+
+    fn is_pod_ready(pod: &Pod) -> bool {
+        pod.status
+            .as_ref()
+            .and_then(|status| status.conditions.as_ref())
+            .is_some_and(|conditions| {
+                conditions
+                    .iter()
+                    .any(|cond| cond.type_ == "Ready" && cond.status == "True")
+            })
+    }
+
+    /// Summarise the state of a set of pods, including why their containers
+    /// are not running (e.g. ImagePullBackOff), to report when we time out
+    fn describe_pods(pods: &[Arc<Pod>]) -> String {
+        if pods.is_empty() {
+            return "no pods".to_string();
+        }
+
+        pods.iter()
+            .map(|pod| {
+                let name = pod.metadata.name.as_deref().unwrap_or("<unknown>");
+                let status = pod.status.as_ref();
+                let phase = status
+                    .and_then(|status| status.phase.as_deref())
+                    .unwrap_or("Unknown");
+                let reasons: Vec<&str> = status
+                    .and_then(|status| status.container_statuses.as_ref())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|cont| cont.state.as_ref()?.waiting.as_ref()?.reason.as_deref())
+                    .collect();
+
+                let ready = if Self::is_pod_ready(pod) {
+                    "ready"
+                } else {
+                    "not ready"
+                };
+                if reasons.is_empty() {
+                    format!("{name}: {phase}, {ready}")
+                } else {
+                    format!("{name}: {phase}, {ready} ({})", reasons.join(", "))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Watch the pods in a namespace that match a label selector, until
+    /// `done` holds for them, or we time out
+    fn wait_for_pods_until(
+        namespace: &str,
+        label: &str,
+        timeout: Option<time::Duration>,
+        done: impl Fn(&[Arc<Pod>]) -> bool,
+    ) -> Result<(), K8sError> {
+        let (runtime, client) = Self::get_kube_client();
+        let pods: Api<Pod> = Api::namespaced(client.clone(), namespace);
+        let (reader, writer) = reflector::store();
+        let mut events = reflector(
+            writer,
+            watcher(pods, watcher::Config::default().labels(label)).default_backoff(),
+        )
+        .boxed();
+
+        let watch = async {
+            while let Some(event) = events.next().await {
+                match event {
+                    // The store is only populated once the initial listing
+                    // is done
+                    Ok(watcher::Event::Init | watcher::Event::InitApply(_)) => {}
+                    Ok(_) => {
+                        if done(&reader.state()) {
+                            return true;
+                        }
+                    }
+                    Err(e) => debug!(
+                        "{}(k8s): error watching pods (label: {label}): {e}",
+                        Env::SYS_NAME
+                    ),
+                }
+            }
+
+            false
+        };
+        let is_done = runtime.block_on(async {
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, watch).await.unwrap_or(false),
+                None => watch.await,
+            }
+        });
+
+        if is_done {
+            return Ok(());
+        }
+
+        Err(K8sError::Timeout {
+            waiting_for: format!("pods (label: {label})"),
+            timeout: timeout.unwrap_or_default(),
+            last_status: Self::describe_pods(&reader.state()),
+        })
+    }
+
+    /// Get a command to run a program as root on a node. If we do not pin
+    /// the experiment to a node, we run the program locally, otherwise we
+    /// run it over SSH on the given node
+    pub fn get_node_sudo_cmd(node: Option<&str>, program: &str) -> Command {
+        match node {
+            Some(node) => {
+                let mut cmd = Command::new("ssh");
+                cmd.args([node, "sudo", program]);
+                cmd
             }
             None => {
-                let stderr =
-                    str::from_utf8(&output.stderr).unwrap_or("sc2-exp(k8s): failed to get stderr");
-                panic!("{}(k8s): kubectl command failed: {stderr}", Env::SYS_NAME);
+                let mut cmd = Command::new("sudo");
+                cmd.arg(program);
+                cmd
             }
-        };
+        }
+    }
+
+    fn is_transient_kubectl_error(stderr: &str) -> bool {
+        let stderr = stderr.to_lowercase();
+        Self::KUBECTL_TRANSIENT_ERRORS
+            .iter()
+            .any(|error| stderr.contains(error))
+    }
+
+    /// Run a kubectl command (feeding it `stdin`, if any). If it fails with
+    /// a transient error, we retry it with exponential back-off, but we
+    /// panic straight away on any other error (e.g. NotFound or an invalid
+    /// spec)
+    fn run_kubectl_with_retries(args: &[&str], stdin: Option<&str>) -> Output {
+        Self::try_run_kubectl_with_retries(args, stdin)
+            .unwrap_or_else(|e| panic!("{}(k8s): {e}", Env::SYS_NAME))
+    }
+
+    /// Same as `run_kubectl_with_retries`, but return a description of any
+    /// (non-transient) error instead of panicking, so that the caller can
+    /// add some context to it
+    fn try_run_kubectl_with_retries(args: &[&str], stdin: Option<&str>) -> Result<Output, String> {
+        let max_retries = Self::get_config().kubectl_max_retries;
+        let mut backoff = time::Duration::from_millis(Self::KUBECTL_INITIAL_BACKOFF_MS);
+        let mut num_retries = 0;
+
+        loop {
+            let mut kubectl = Command::new(Self::get_kubectl_cmd())
+                .args(args)
+                .stdin(if stdin.is_some() {
+                    Stdio::piped()
+                } else {
+                    Stdio::null()
+                })
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .expect("sc2-exp(k8s): failed to spawn kubectl command");
+
+            if let Some(stdin) = stdin {
+                kubectl
+                    .stdin
+                    .as_mut()
+                    .expect("sc2-exp(k8s): failed to open stdin for kubectl")
+                    .write_all(stdin.as_bytes())
+                    .expect("sc2-exp(k8s): failed to feed kubectl through stdin");
+            }
+
+            let output = kubectl
+                .wait_with_output()
+                .expect("sc2-exp(k8s): failed to run kubectl command");
+            if output.status.success() {
+                return Ok(output);
+            }
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if num_retries >= max_retries || !Self::is_transient_kubectl_error(&stderr) {
+                return Err(match output.status.code() {
+                    Some(code) => format!(
+                        "kubectl exited with error (code: {code}): stdout: {stdout} - stderr: {stderr}"
+                    ),
+                    None => format!("kubectl command failed: stdout: {stdout} - stderr: {stderr}"),
+                });
+            }
+
+            num_retries += 1;
+            warn!(
+                "{}(k8s): kubectl failed with transient error (retry {num_retries}/{max_retries} in {}ms): {}",
+                Env::SYS_NAME,
+                backoff.as_millis(),
+                stderr.trim()
+            );
+            thread::sleep(backoff);
+            backoff *= 2;
+        }
+    }
+
+    pub fn run_kubectl_cmd(cmd: &str) -> String {
+        debug!("{}(k8s): running kubectl command: {cmd}", Env::SYS_NAME);
+        let args: Vec<&str> = cmd.split_whitespace().collect();
+
+        let output = Self::run_kubectl_with_retries(&args, None);
 
         String::from_utf8(output.stdout)
             .expect("sc2-eval(k8s): failed to convert kube command output to string")
@@ -54,33 +375,223 @@ impl K8s {
             .to_string()
     }
 
-    pub fn wait_for_pods(namespace: &str, label: &str, num_expected: usize) {
-        loop {
-            thread::sleep(time::Duration::from_secs(2));
+    /// Get the candidate paths to a pod's cgroup on its node. The path
+    /// depends on the pod's QoS class, and on the kubelet's cgroup driver
+    /// (systemd or cgroupfs), so we return one for each driver
+    fn get_pod_cgroup_paths(pod: &Pod) -> Option<Vec<String>> {
+        let uid = pod.metadata.uid.as_deref()?;
+        let qos_class = pod
+            .status
+            .as_ref()
+            .and_then(|status| status.qos_class.as_deref())
+            .unwrap_or("BestEffort")
+            .to_lowercase();
 
-            let output = Self::run_kubectl_cmd(&format!("-n {namespace} get pods -l {label} -o jsonpath='{{..status.conditions[?(@.type==\"Ready\")].status}}'"));
-            let values: Vec<&str> = output.split_whitespace().collect();
+        // systemd escapes the dashes in the pod's uid as underscores
+        let systemd_uid = uid.replace('-', "_");
+        Some(match qos_class.as_str() {
+            "guaranteed" => vec![
+                format!("{}/kubepods.slice/kubepods-pod{systemd_uid}.slice", Self::CGROUP_ROOT),
+                format!("{}/kubepods/pod{uid}", Self::CGROUP_ROOT),
+            ],
+            qos_class => vec![
+                format!(
+                    "{}/kubepods.slice/kubepods-{qos_class}.slice/kubepods-{qos_class}-pod{systemd_uid}.slice",
+                    Self::CGROUP_ROOT
+                ),
+                format!("{}/kubepods/{qos_class}/pod{uid}", Self::CGROUP_ROOT),
+            ],
+        })
+    }
 
-            debug!(
-                "{}(k8s): waiting for {num_expected} pods (label: {label}) to be ready...",
-                Env::SYS_NAME
-            );
-            if values.len() != num_expected {
-                debug!(
-                    "{}(k8s): not enough pods: {} != {num_expected}",
-                    Env::SYS_NAME,
-                    values.len()
-                );
+    /// Get the total peak memory usage (in KB) of a service's pods, as
+    /// reported by their cgroups (i.e. `memory.peak`, which needs cgroup v2)
+    /// on the node that they run on. For the confidential baselines, the
+    /// pod's cgroup includes the guest's memory. As the peak is since the
+    /// pod started, we read it after the run, but before scaling to zero
+    pub fn get_pods_peak_memory_kb(service_name: &str, node: Option<&str>) -> Option<u64> {
+        let (runtime, client) = Self::get_kube_client();
+        let pods: Api<Pod> = Api::namespaced(client.clone(), Self::namespace());
+        let label = format!("apps.sc2.io/name={service_name}");
+        let pod_list = runtime
+            .block_on(pods.list(&ListParams::default().labels(&label)))
+            .ok()?;
+
+        let mut peak_memory_kb: Option<u64> = None;
+        for pod in pod_list
+            .iter()
+            .filter(|pod| pod.metadata.deletion_timestamp.is_none())
+        {
+            let Some(cgroup_paths) = Self::get_pod_cgroup_paths(pod) else {
                 continue;
+            };
+
+            // Only one of the candidate paths exists, so we ignore the
+            // command's exit code, and parse whatever we could read
+            let output = Self::get_node_sudo_cmd(node, "cat")
+                .args(
+                    cgroup_paths
+                        .iter()
+                        .map(|path| format!("{path}/memory.peak")),
+                )
+                .output()
+                .ok()?;
+            let pod_peak_bytes = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(|line| line.trim().parse::<u64>().ok());
+            match pod_peak_bytes {
+                Some(pod_peak_bytes) => {
+                    *peak_memory_kb.get_or_insert(0) += pod_peak_bytes / 1024;
+                }
+                None => debug!(
+                    "{}(k8s): failed to read memory.peak for pod {} ({cgroup_paths:?}): {}",
+                    Env::SYS_NAME,
+                    pod.metadata.name.as_deref().unwrap_or("<unknown>"),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
             }
+        }
 
-            if !values.iter().all(|&item| item == "'True'") {
-                debug!("{}(eval): not enough pods in 'Ready' state", Env::SYS_NAME);
-                continue;
+        debug!(
+            "{}(k8s): service '{service_name}' peak memory: {peak_memory_kb:?} KB",
+            Env::SYS_NAME
+        );
+
+        peak_memory_kb
+    }
+
+    /// Get the runtime classes that the cluster supports, with the node
+    /// selector (i.e. the node labels) that each of them schedules pods with
+    pub fn get_runtime_classes() -> BTreeMap<String, BTreeMap<String, String>> {
+        let (runtime, client) = Self::get_kube_client();
+        let runtime_classes: Api<RuntimeClass> = Api::all(client.clone());
+
+        runtime
+            .block_on(runtime_classes.list(&ListParams::default()))
+            .unwrap_or_else(|e| {
+                panic!(
+                    "{}(k8s): failed to list runtime classes: {e}",
+                    Env::SYS_NAME
+                )
+            })
+            .into_iter()
+            .filter_map(|runtime_class| {
+                let node_selector = runtime_class
+                    .scheduling
+                    .and_then(|scheduling| scheduling.node_selector)
+                    .unwrap_or_default();
+                Some((runtime_class.metadata.name?, node_selector))
+            })
+            .collect()
+    }
+
+    /// Get the labels of each node in the cluster (by node name)
+    pub fn get_node_labels() -> BTreeMap<String, BTreeMap<String, String>> {
+        let (runtime, client) = Self::get_kube_client();
+        let nodes: Api<Node> = Api::all(client.clone());
+
+        runtime
+            .block_on(nodes.list(&ListParams::default()))
+            .unwrap_or_else(|e| panic!("{}(k8s): failed to list nodes: {e}", Env::SYS_NAME))
+            .into_iter()
+            .filter_map(|node| {
+                Some((
+                    node.metadata.name?,
+                    node.metadata.labels.unwrap_or_default(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Get the (tail of the) logs of the user-container and queue-proxy of a
+    /// service's pods, to help debug failed runs. We only use them to report
+    /// other failures, so we return any error we hit as part of the logs
+    pub fn get_pod_logs(service_name: &str) -> String {
+        let (runtime, client) = Self::get_kube_client();
+        let pods: Api<Pod> = Api::namespaced(client.clone(), Self::namespace());
+        let label = format!("apps.sc2.io/name={service_name}");
+
+        runtime.block_on(async {
+            let pod_list = match pods.list(&ListParams::default().labels(&label)).await {
+                Ok(pod_list) => pod_list,
+                Err(e) => return format!("failed to list pods (label: {label}): {e}"),
+            };
+            if pod_list.items.is_empty() {
+                return format!("no pods found (label: {label})");
             }
 
-            break;
-        }
+            let mut logs = String::new();
+            for pod in pod_list {
+                let pod_name = pod.metadata.name.unwrap_or_default();
+                for container in ["user-container", "queue-proxy"] {
+                    let log_params = LogParams {
+                        container: Some(container.to_string()),
+                        tail_lines: Some(Self::POD_LOGS_TAIL_LINES),
+                        ..Default::default()
+                    };
+                    let container_logs = pods
+                        .logs(&pod_name, &log_params)
+                        .await
+                        .unwrap_or_else(|e| format!("failed to get logs: {e}"));
+                    logs.push_str(&format!(
+                        "==> {pod_name}/{container} <==\n{}\n",
+                        container_logs.trim_end()
+                    ));
+                }
+            }
+
+            logs
+        })
+    }
+
+    pub fn wait_for_pods(
+        namespace: &str,
+        label: &str,
+        num_expected: usize,
+        timeout: Option<time::Duration>,
+    ) -> Result<(), K8sError> {
+        debug!(
+            "{}(k8s): waiting for {num_expected} pods (label: {label}) to be ready...",
+            Env::SYS_NAME
+        );
+        Self::wait_for_pods_until(namespace, label, timeout, |pods| {
+            let num_ready = pods.iter().filter(|pod| Self::is_pod_ready(pod)).count();
+            debug!(
+                "{}(k8s): {num_ready}/{} pods ready (expected: {num_expected})",
+                Env::SYS_NAME,
+                pods.len()
+            );
+
+            pods.len() == num_expected && num_ready == num_expected
+        })
+    }
+
+    /// Wait until at least a number of pods are ready. Unlike
+    /// `wait_for_pods`, we tolerate extra pods (e.g. if the autoscaler
+    /// overshoots), and do not count the ones that are terminating
+    pub fn wait_for_ready_pods(
+        namespace: &str,
+        label: &str,
+        num_expected: usize,
+        timeout: Option<time::Duration>,
+    ) -> Result<(), K8sError> {
+        debug!(
+            "{}(k8s): waiting for at least {num_expected} pods (label: {label}) to be ready...",
+            Env::SYS_NAME
+        );
+        Self::wait_for_pods_until(namespace, label, timeout, |pods| {
+            let num_ready = pods
+                .iter()
+                .filter(|pod| pod.metadata.deletion_timestamp.is_none() && Self::is_pod_ready(pod))
+                .count();
+            debug!(
+                "{}(k8s): {num_ready}/{} pods ready (expected at least: {num_expected})",
+                Env::SYS_NAME,
+                pods.len()
+            );
+
+            num_ready >= num_expected
+        })
     }
 
     fn template_yaml(yaml_path: &PathBuf, env_vars: &BTreeMap<&str, String>) -> String {
@@ -91,72 +602,207 @@ impl K8s {
         let yaml_content =
             fs::read_to_string(yaml_path).expect("sc2-exp(k8s): failed to read yaml");
 
-        // Use envsubst to substitute environment variables in the YAML
-        let mut envsubst_cmd = Command::new("envsubst");
-        for (key, value) in env_vars {
-            envsubst_cmd.env(key, value);
+        // Substitute the env. vars (either as ${VAR} or $VAR) in the YAML.
+        // Like envsubst, we substitute variables that we do not know about
+        // with an empty string
+        let var_regex =
+            Regex::new(r"\$(?:\{(?P<braced>[A-Za-z_]\w*)\}|(?P<bare>[A-Za-z_]\w*))").unwrap();
+        let result_str = var_regex.replace_all(&yaml_content, |caps: &Captures| {
+            let var = caps.name("braced").or(caps.name("bare")).unwrap().as_str();
+            env_vars.get(var).cloned().unwrap_or_else(|| {
+                warn!(
+                    "{}(k8s): no value for variable ${var} in {}",
+                    Env::SYS_NAME,
+                    yaml_path.display()
+                );
+                String::new()
+            })
+        });
+
+        // Then, tweak the pod spec in each of the YAML documents
+        let mut docs = Vec::new();
+        for doc in serde_norway::Deserializer::from_str(&result_str) {
+            let mut value = Value::deserialize(doc).unwrap_or_else(|e| {
+                panic!(
+                    "{}(k8s): failed to parse templated yaml from {}: {e}",
+                    Env::SYS_NAME,
+                    yaml_path.display()
+                )
+            });
+            if value.is_null() {
+                continue;
+            }
+
+            // If pinning the service to a node, add a node selector to the
+            // pod spec (note that Knative must have the
+            // kubernetes.podspec-nodeselector feature enabled)
+            if let Some(node) = env_vars.get("NODE_NAME") {
+                Self::edit_pod_specs(&mut value, &mut |pod_spec| {
+                    let mut node_selector = Mapping::new();
+                    node_selector.insert("kubernetes.io/hostname".into(), node.as_str().into());
+                    pod_spec.insert("nodeSelector".into(), node_selector.into());
+                });
+            }
+
+            // If running the `runc` baseline, we must drop the runtime class
+            // altogether
+            if env_vars.get("RUNTIME_CLASS_NAME").unwrap() == "runc" {
+                Self::edit_pod_specs(&mut value, &mut |pod_spec| {
+                    pod_spec.remove("runtimeClassName");
+                });
+            }
+
+            // If scaling the service out, make sure that each concurrent
+            // request gets its own replica, and that the autoscaler does not
+            // go beyond the replicas that we measure
+            if let Some(max_scale) = env_vars.get("MAX_SCALE") {
+                Self::edit_pod_template_annotations(&mut value, &mut |annotations| {
+                    annotations.insert("autoscaling.knative.dev/target".into(), "1".into());
+                    annotations.insert(
+                        "autoscaling.knative.dev/max-scale".into(),
+                        max_scale.as_str().into(),
+                    );
+                });
+            }
+
+            docs.push(serde_norway::to_string(&value).unwrap());
         }
 
-        let mut envsubst = envsubst_cmd
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .spawn()
-            .expect("sc2-exp(k8s): failed to start envsubst");
-
-        envsubst
-            .stdin
-            .as_mut()
-            .expect("sc2-exp(k8s): failed to open stdin for envsubst")
-            .write_all(yaml_content.as_bytes())
-            .expect("sc2-exp(k8s): failed to write to envsubst");
-
-        // Collect the output (substituted YAML)
-        let result = envsubst
-            .wait_with_output()
-            .expect("sc2-exp(k8s): failed to read envsubst result");
-
-        let result_str = String::from_utf8(result.stdout)
-            .expect("sc2-exp(k8s): failed to convert envsubst output to string");
-
-        // If running the `runc` baseline, we must drop the runtime class line
-        // altogether
-        if env_vars.get("RUNTIME_CLASS_NAME").unwrap() == "runc" {
-            return result_str
-                .lines()
-                .filter(|line| !line.trim_start().starts_with("runtimeClassName"))
+        docs.join("---\n")
+    }
+
+    /// Apply an edit to every pod spec (i.e. every mapping with a list of
+    /// containers) in a YAML document, however deep it is nested
+    fn edit_pod_specs(value: &mut Value, edit: &mut impl FnMut(&mut Mapping)) {
+        match value {
+            Value::Mapping(mapping) => {
+                if mapping.contains_key("containers") {
+                    edit(mapping);
+                }
+                for (_, value) in mapping.iter_mut() {
+                    Self::edit_pod_specs(value, edit);
+                }
+            }
+            Value::Sequence(sequence) => {
+                for value in sequence {
+                    Self::edit_pod_specs(value, edit);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Apply an edit to the annotations of every pod template (i.e. every
+    /// mapping whose spec is a pod spec) in a YAML document, creating them
+    /// if need be. Knative reads its per-revision settings (e.g. autoscaling)
+    /// from these annotations
+    fn edit_pod_template_annotations(value: &mut Value, edit: &mut impl FnMut(&mut Mapping)) {
+        match value {
+            Value::Mapping(mapping) => {
+                let is_pod_template = mapping
+                    .get("spec")
+                    .and_then(Value::as_mapping)
+                    .is_some_and(|spec| spec.contains_key("containers"));
+                if is_pod_template {
+                    let metadata = mapping
+                        .entry("metadata".into())
+                        .or_insert_with(|| Mapping::new().into());
+                    if let Some(metadata) = metadata.as_mapping_mut() {
+                        let annotations = metadata
+                            .entry("annotations".into())
+                            .or_insert_with(|| Mapping::new().into());
+                        if let Some(annotations) = annotations.as_mapping_mut() {
+                            edit(annotations);
+                        }
+                    }
+                }
+                for (_, value) in mapping.iter_mut() {
+                    Self::edit_pod_template_annotations(value, edit);
+                }
+            }
+            Value::Sequence(sequence) => {
+                for value in sequence {
+                    Self::edit_pod_template_annotations(value, edit);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Summarise the status conditions of a Knative object (e.g. a service
+    /// or a revision), to report when we time out
+    fn describe_knative_object(obj: Option<&DynamicObject>) -> String {
+        let Some(obj) = obj else {
+            return "not found".to_string();
+        };
+
+        match obj.data["status"]["conditions"].as_array() {
+            Some(conditions) => conditions
+                .iter()
+                .map(|cond| {
+                    format!(
+                        "{}={} ({})",
+                        cond["type"].as_str().unwrap_or("Unknown"),
+                        cond["status"].as_str().unwrap_or("Unknown"),
+                        cond["message"].as_str().unwrap_or("no message")
+                    )
+                })
                 .collect::<Vec<_>>()
-                .join("\n");
+                .join(", "),
+            None => "no status yet".to_string(),
         }
+    }
 
-        result_str
+    /// Get the API for a kind of Knative serving object (e.g. Service or
+    /// Revision) in our namespace
+    fn get_knative_api(kind: &str) -> Api<DynamicObject> {
+        let (_, client) = Self::get_kube_client();
+        let resource =
+            ApiResource::from_gvk(&GroupVersionKind::gvk("serving.knative.dev", "v1", kind));
+
+        Api::namespaced_with(client.clone(), Self::namespace(), &resource)
     }
 
-    fn get_knative_service_ip(service_name: &str) -> String {
-        // First, wait until the service is ready
-        loop {
-            let output = Self::run_kubectl_cmd(
-                &format!("-n {} get ksvc -o jsonpath={{.items[?(@.metadata.name==\"{service_name}\")].metadata.name}}", Env::K8S_NAMESPACE)
-            );
+    pub fn get_knative_service_ip(
+        service_name: &str,
+        timeout: Option<time::Duration>,
+    ) -> Result<String, K8sError> {
+        let (runtime, _) = Self::get_kube_client();
+        let ksvcs = Self::get_knative_api("Service");
+        fn get_url(ksvc: Option<&DynamicObject>) -> Option<&str> {
+            ksvc.and_then(|ksvc| ksvc.data["status"]["url"].as_str())
+        }
 
-            debug!(
-                "{}: waiting for Knative serice to be ready '{service_name}': out: {output}",
-                Env::SYS_NAME
-            );
-            let values: Vec<&str> = output.split_whitespace().collect();
-            if values.len() == 1 && values[0] == service_name {
-                break;
+        // First, wait until the service has a URL
+        debug!(
+            "{}(k8s): waiting for Knative service to be ready '{service_name}'",
+            Env::SYS_NAME
+        );
+        let has_url = await_condition(
+            ksvcs.clone(),
+            service_name,
+            |ksvc: Option<&DynamicObject>| get_url(ksvc).is_some(),
+        );
+        let ksvc = runtime.block_on(async {
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, has_url).await.ok(),
+                None => Some(has_url.await),
             }
+        });
 
-            thread::sleep(time::Duration::from_secs(2));
-        }
+        match ksvc {
+            Some(Ok(ksvc)) => Ok(get_url(ksvc.as_ref()).unwrap_or_default().to_string()),
+            Some(Err(e)) => Err(K8sError::Wait(e)),
+            None => {
+                let ksvc = runtime.block_on(ksvcs.get_opt(service_name)).ok().flatten();
 
-        Self::run_kubectl_cmd(
-            format!(
-                "-n {} get ksvc {service_name} --output=custom-columns=URL:.status.url --no-headers",
-                Env::K8S_NAMESPACE
-            )
-            .as_str(),
-        )
+                Err(K8sError::Timeout {
+                    waiting_for: format!("Knative service '{service_name}'"),
+                    timeout: timeout.unwrap_or_default(),
+                    last_status: Self::describe_knative_object(ksvc.as_ref()),
+                })
+            }
+        }
     }
 
     fn template_yaml_and_run_cmd(
@@ -167,51 +813,45 @@ impl K8s {
         // First, template the YAML file with the provided env. vars
         let templated_yaml = Self::template_yaml(yaml_path, env_vars);
 
-        let mut kubectl = Command::new(Self::get_kubectl_cmd())
-            .arg(cmd)
-            .arg("-f")
-            .arg("-")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()
-            .expect("sc2-exp(k8s): failed to start kubectl apply");
-
-        kubectl
-            .stdin
-            .as_mut()
-            .expect("sc2-exp(k8s): failed to open stdin for kubectl")
-            .write_all(templated_yaml.as_bytes())
-            .expect("sc2-exp(k8s): failed to feed kubectl through stdin");
-
-        // Check if the kubectl command succeeded
-        let output = kubectl
-            .wait_with_output()
-            .expect("sc2-exp(k8s): failed to run kubectl command");
-
-        match output.status.code() {
-            Some(0) => {}
-            Some(code) => {
-                let stderr =
-                    str::from_utf8(&output.stderr).unwrap_or("sc2-exp(k8s): failed to get stderr");
-                let stdout =
-                    str::from_utf8(&output.stdout).unwrap_or("sc2-exp(k8s): failed to get stdout");
-                panic!(
-                    "{}(k8s): kubectl exited with error (code: {code}): stdout: {stdout} - stderr: {stderr}",
-                    Env::SYS_NAME
+        match Self::try_run_kubectl_with_retries(&[cmd, "-f", "-"], Some(&templated_yaml)) {
+            Ok(output) => {
+                debug!(
+                    "{}(k8s): kubectl {cmd} stdout: {}",
+                    Env::SYS_NAME,
+                    String::from_utf8_lossy(&output.stdout).trim()
                 );
-            }
-            None => {
-                let stdout =
-                    str::from_utf8(&output.stdout).unwrap_or("sc2-exp(k8s): failed to get stdout");
-                let stderr =
-                    str::from_utf8(&output.stderr).unwrap_or("sc2-exp(k8s): failed to get stderr");
-                panic!(
-                    "{}(k8s): kubectl command failed: stdout: {stdout} - stderr: {stderr}",
-                    Env::SYS_NAME
+                debug!(
+                    "{}(k8s): kubectl {cmd} stderr: {}",
+                    Env::SYS_NAME,
+                    String::from_utf8_lossy(&output.stderr).trim()
                 );
             }
-        };
+            Err(e) => {
+                // Keep the YAML that we sent, as the error may well come from
+                // a templating bug
+                let mut yaml_dump = env::temp_dir();
+                yaml_dump.push(format!(
+                    "{}-{cmd}-{}.yaml",
+                    Env::SYS_NAME,
+                    env_vars
+                        .get("KSERVICE_NAME")
+                        .map_or("service", String::as_str)
+                ));
+                match fs::write(&yaml_dump, &templated_yaml) {
+                    Ok(()) => panic!(
+                        "{}(k8s): failed to {cmd} {} (templated YAML at: {}): {e}",
+                        Env::SYS_NAME,
+                        yaml_path.display(),
+                        yaml_dump.display()
+                    ),
+                    Err(dump_e) => panic!(
+                        "{}(k8s): failed to {cmd} {} (and to dump the templated YAML: {dump_e}): {e}",
+                        Env::SYS_NAME,
+                        yaml_path.display()
+                    ),
+                }
+            }
+        }
     }
 
     /// Deploy Knative service from `yaml_path`, templated with `env_vars`, and
@@ -219,42 +859,173 @@ impl K8s {
     pub fn deploy_knative_service(
         yaml_path: &PathBuf,
         env_vars: &BTreeMap<&str, String>,
-    ) -> String {
+        timeout: Option<time::Duration>,
+    ) -> Result<String, K8sError> {
         Self::template_yaml_and_run_cmd("apply", yaml_path, env_vars);
 
         // Return the IP
-        Self::get_knative_service_ip(&env_vars["KSERVICE_NAME"])
+        Self::get_knative_service_ip(&env_vars["KSERVICE_NAME"], timeout)
     }
 
     /// Get the Knative deployment ID given a service name
     pub fn get_knative_deployment_id(service_name: &str) -> String {
         Self::run_kubectl_cmd(
             &format!("-n {} get deployments -l apps.sc2.io/name={service_name} -o jsonpath={{.items..metadata.name}}",
-            Env::K8S_NAMESPACE
+            Self::namespace()
             )
         )
     }
 
-    pub fn scale_knative_service_to_zero(service_name: &str) {
+    pub fn scale_knative_service_to_zero(
+        service_name: &str,
+        timeout: Option<time::Duration>,
+    ) -> Result<(), K8sError> {
         // Wait for the scale-to-zero to take effect
-        loop {
-            let output = Self::run_kubectl_cmd(
-                &format!("-n {} get pods -l apps.sc2.io/name={service_name} -o jsonpath={{..status.conditions[?(@.type==\"Ready\")].status}}",
-                Env::K8S_NAMESPACE
+        debug!(
+            "{}(k8s): waiting for a scale-down service '{service_name}'",
+            Env::SYS_NAME
+        );
+        let deadline = timeout.map(|timeout| time::Instant::now() + timeout);
+        Self::wait_for_pods_until(
+            Self::namespace(),
+            &format!("apps.sc2.io/name={service_name}"),
+            timeout,
+            |pods| pods.is_empty(),
+        )?;
+
+        // Knative may remove the pods while the revision is still draining,
+        // so also wait for Knative to report the revision as inactive, not to
+        // measure the next cold start on a half torn-down data path
+        let (runtime, _) = Self::get_kube_client();
+        let ksvc = runtime
+            .block_on(Self::get_knative_api("Service").get_opt(service_name))
+            .ok()
+            .flatten();
+        let Some(revision_name) = ksvc
+            .as_ref()
+            .and_then(|ksvc| ksvc.data["status"]["latestReadyRevisionName"].as_str())
+        else {
+            return Ok(());
+        };
+
+        fn is_scaled_to_zero(revision: Option<&DynamicObject>) -> bool {
+            revision.is_some_and(|revision| {
+                let status = &revision.data["status"];
+                let is_inactive = status["conditions"].as_array().is_some_and(|conditions| {
+                    conditions
+                        .iter()
+                        .any(|cond| cond["type"] == "Active" && cond["status"] == "False")
+                });
+
+                is_inactive && status["actualReplicas"].as_u64().unwrap_or(0) == 0
+            })
+        }
+
+        let revisions = Self::get_knative_api("Revision");
+        let scaled_to_zero = await_condition(revisions.clone(), revision_name, is_scaled_to_zero);
+        let revision = runtime.block_on(async {
+            match deadline {
+                Some(deadline) => tokio::time::timeout(
+                    deadline.saturating_duration_since(time::Instant::now()),
+                    scaled_to_zero,
                 )
-            );
-            debug!(
-                "{}: waiting for a scale-down service '{service_name}': out: {output}",
+                .await
+                .ok(),
+                None => Some(scaled_to_zero.await),
+            }
+        });
+
+        match revision {
+            Some(Ok(_)) => Ok(()),
+            Some(Err(e)) => Err(K8sError::Wait(e)),
+            None => {
+                let revision = runtime
+                    .block_on(revisions.get_opt(revision_name))
+                    .ok()
+                    .flatten();
+
+                Err(K8sError::Timeout {
+                    waiting_for: format!("Knative revision '{revision_name}' to scale to zero"),
+                    timeout: timeout.unwrap_or_default(),
+                    last_status: Self::describe_knative_object(revision.as_ref()),
+                })
+            }
+        }
+    }
+
+    /// Keep (at least) a number of replicas of a Knative service running, by
+    /// setting its min-scale annotation, and wait for all of them to be ready
+    pub fn scale_knative_service(
+        service_name: &str,
+        min_scale: u32,
+        timeout: Option<time::Duration>,
+    ) -> Result<(), K8sError> {
+        let patch = format!(
+            r#"{{"spec":{{"template":{{"metadata":{{"annotations":{{"autoscaling.knative.dev/min-scale":"{min_scale}"}}}}}}}}}}"#
+        );
+        let generation = Self::run_kubectl_cmd(&format!(
+            "-n {} patch ksvc {service_name} --type merge -p {patch} -o jsonpath={{.metadata.generation}}",
+            Self::namespace()
+        ));
+        let generation: i64 = generation.trim().parse().unwrap_or_else(|e| {
+            panic!(
+                "{}(k8s): failed to parse generation of service '{service_name}' ({generation}): {e}",
                 Env::SYS_NAME
-            );
-            let values: Vec<&str> = output.split_whitespace().collect();
+            )
+        });
 
-            if values.is_empty() {
-                break;
+        // Changing the template creates a new revision, so only count its
+        // pods, and not the ones of the previous revision that Knative has
+        // not torn down yet
+        debug!(
+            "{}(k8s): waiting for a new revision of service '{service_name}'",
+            Env::SYS_NAME
+        );
+        let deadline = timeout.map(|timeout| time::Instant::now() + timeout);
+        fn get_new_revision(ksvc: Option<&DynamicObject>, generation: i64) -> Option<&str> {
+            let status = &ksvc?.data["status"];
+            if status["observedGeneration"].as_i64()? < generation {
+                return None;
             }
 
-            thread::sleep(time::Duration::from_secs(2));
+            status["latestCreatedRevisionName"].as_str()
         }
+
+        let (runtime, _) = Self::get_kube_client();
+        let ksvcs = Self::get_knative_api("Service");
+        let has_new_revision = await_condition(
+            ksvcs.clone(),
+            service_name,
+            move |ksvc: Option<&DynamicObject>| get_new_revision(ksvc, generation).is_some(),
+        );
+        let ksvc = runtime.block_on(async {
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, has_new_revision).await.ok(),
+                None => Some(has_new_revision.await),
+            }
+        });
+        let revision_name = match ksvc {
+            Some(Ok(ksvc)) => get_new_revision(ksvc.as_ref(), generation)
+                .unwrap()
+                .to_string(),
+            Some(Err(e)) => return Err(K8sError::Wait(e)),
+            None => {
+                let ksvc = runtime.block_on(ksvcs.get_opt(service_name)).ok().flatten();
+
+                return Err(K8sError::Timeout {
+                    waiting_for: format!("a new revision of Knative service '{service_name}'"),
+                    timeout: timeout.unwrap_or_default(),
+                    last_status: Self::describe_knative_object(ksvc.as_ref()),
+                });
+            }
+        };
+
+        Self::wait_for_pods(
+            Self::namespace(),
+            &format!("serving.knative.dev/revision={revision_name}"),
+            min_scale as usize,
+            deadline.map(|deadline| deadline.saturating_duration_since(time::Instant::now())),
+        )
     }
 
     pub fn delete_knative_service(yaml_path: &PathBuf, env_vars: &BTreeMap<&str, String>) {
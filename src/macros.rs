@@ -0,0 +1,42 @@
+/// Define an enum whose variants we display and parse with a given name, and
+/// that we can iterate over (in declaration order) with `iter_variants`, from
+/// a single list of variants. Attributes (e.g. derives) are passed through
+macro_rules! named_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($variant:ident => $display:literal),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($variant),+
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    $($name::$variant => write!(f, $display)),+
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = ();
+
+            fn from_str(input: &str) -> Result<$name, Self::Err> {
+                match input {
+                    $($display => Ok($name::$variant),)+
+                    _ => Err(()),
+                }
+            }
+        }
+
+        impl $name {
+            pub fn iter_variants() -> std::slice::Iter<'static, $name> {
+                static VARIANTS: &[$name] = &[$($name::$variant),+];
+                VARIANTS.iter()
+            }
+        }
+    };
+}
@@ -1,15 +1,24 @@
+use crate::env::Env;
 use crate::experiment::{AvailableExperiments, Exp, ExpRunArgs};
-use crate::plot::Plot;
+use crate::kubernetes::K8s;
+use crate::plot::{Plot, PlotArgs};
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[macro_use]
+mod macros;
 
 pub mod containerd;
 pub mod cri;
 pub mod env;
 pub mod experiment;
 pub mod kubernetes;
+pub mod netem;
+pub mod notify;
 pub mod plot;
 
 #[derive(Parser)]
+#[command(version = Env::VERSION)]
 struct Cli {
     // The name of the task to execute
     #[clap(subcommand)]
@@ -17,18 +26,52 @@ struct Cli {
 
     #[arg(short, long, global = true)]
     debug: bool,
+
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    #[arg(long, global = true, value_name = "DIR", visible_alias = "output-dir")]
+    results_dir: Option<PathBuf>,
+
+    #[arg(long, global = true, value_name = "DIR")]
+    apps_dir: Option<PathBuf>,
+
+    #[arg(long, global = true)]
+    namespace: Option<String>,
+
+    #[arg(long, global = true, value_name = "UNIT")]
+    containerd_unit: Option<String>,
+
+    #[arg(long, global = true, value_name = "UNIT")]
+    snapshotter_unit: Option<String>,
+
+    #[arg(long, global = true, value_name = "DIR")]
+    snapshotter_root: Option<PathBuf>,
+
+    #[arg(long, global = true, value_name = "PATH")]
+    kubectl_path: Option<PathBuf>,
+
+    #[arg(long, global = true, default_value_t = K8s::DEFAULT_KUBECTL_MAX_RETRIES)]
+    kubectl_max_retries: u32,
 }
 
 #[derive(Debug, Subcommand)]
 enum ExpSubCommand {
     /// Run
-    Run(ExpRunArgs),
+    Run(Box<ExpRunArgs>),
+    /// List the configurations that a run would execute
+    List(Box<ExpRunArgs>),
     /// Plot
-    Plot {},
+    Plot(PlotArgs),
 }
 
 #[derive(Debug, Subcommand)]
 enum ExpCommand {
+    /// Evaluate the sensitivity of image pulls to the registry's RTT
+    RegistryRtt {
+        #[command(subcommand)]
+        exp_sub_command: ExpSubCommand,
+    },
     /// Evaluate the start-up latency
     StartUp {
         #[command(subcommand)]
@@ -39,6 +82,11 @@ enum ExpCommand {
         #[command(subcommand)]
         exp_sub_command: ExpSubCommand,
     },
+    /// Evaluate the throughput of a warm service under concurrent requests
+    Throughput {
+        #[command(subcommand)]
+        exp_sub_command: ExpSubCommand,
+    },
 }
 
 fn main() {
@@ -55,15 +103,54 @@ fn main() {
             .init();
     }
 
+    let mut env_config = Env::from_config(cli.config.as_deref());
+    if let Some(results_dir) = &cli.results_dir {
+        env_config.results_root = Some(results_dir.clone());
+    }
+    if let Some(apps_dir) = &cli.apps_dir {
+        env_config.apps_root = Some(apps_dir.clone());
+    }
+    if let Some(unit) = &cli.containerd_unit {
+        env_config.containerd_unit = unit.clone();
+    }
+    if let Some(unit) = &cli.snapshotter_unit {
+        env_config.snapshotter_unit = unit.clone();
+    }
+    if let Some(snapshotter_root) = &cli.snapshotter_root {
+        env_config.snapshotter_root = snapshotter_root.clone();
+    }
+    Env::configure(env_config);
+    K8s::configure(
+        cli.namespace.as_deref().unwrap_or(Env::k8s_namespace()),
+        cli.kubectl_path.clone(),
+        cli.kubectl_max_retries,
+    );
+
     match &cli.task {
+        ExpCommand::RegistryRtt {
+            exp_sub_command: eval_sub_command,
+        } => match eval_sub_command {
+            ExpSubCommand::Run(run_args) => {
+                Exp::run(&AvailableExperiments::RegistryRtt, run_args);
+            }
+            ExpSubCommand::List(run_args) => {
+                Exp::list(&AvailableExperiments::RegistryRtt, run_args);
+            }
+            ExpSubCommand::Plot(plot_args) => {
+                Plot::plot(&AvailableExperiments::RegistryRtt, plot_args);
+            }
+        },
         ExpCommand::ScaleOut {
             exp_sub_command: eval_sub_command,
         } => match eval_sub_command {
             ExpSubCommand::Run(run_args) => {
                 Exp::run(&AvailableExperiments::ScaleOut, run_args);
             }
-            ExpSubCommand::Plot {} => {
-                Plot::plot(&AvailableExperiments::ScaleOut);
+            ExpSubCommand::List(run_args) => {
+                Exp::list(&AvailableExperiments::ScaleOut, run_args);
+            }
+            ExpSubCommand::Plot(plot_args) => {
+                Plot::plot(&AvailableExperiments::ScaleOut, plot_args);
             }
         },
         ExpCommand::StartUp {
@@ -72,8 +159,24 @@ fn main() {
             ExpSubCommand::Run(run_args) => {
                 Exp::run(&AvailableExperiments::StartUp, run_args);
             }
-            ExpSubCommand::Plot {} => {
-                Plot::plot(&AvailableExperiments::StartUp);
+            ExpSubCommand::List(run_args) => {
+                Exp::list(&AvailableExperiments::StartUp, run_args);
+            }
+            ExpSubCommand::Plot(plot_args) => {
+                Plot::plot(&AvailableExperiments::StartUp, plot_args);
+            }
+        },
+        ExpCommand::Throughput {
+            exp_sub_command: eval_sub_command,
+        } => match eval_sub_command {
+            ExpSubCommand::Run(run_args) => {
+                Exp::run(&AvailableExperiments::Throughput, run_args);
+            }
+            ExpSubCommand::List(run_args) => {
+                Exp::list(&AvailableExperiments::Throughput, run_args);
+            }
+            ExpSubCommand::Plot(plot_args) => {
+                Plot::plot(&AvailableExperiments::Throughput, plot_args);
             }
         },
     }
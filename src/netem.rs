@@ -0,0 +1,177 @@
+use crate::env::Env;
+use log::{debug, warn};
+use std::{net::ToSocketAddrs, process::Command, str};
+
+#[derive(Debug)]
+pub struct Netem {}
+
+impl Netem {
+    fn run_tc_cmd(args: &[&str]) -> String {
+        debug!("{}(netem): running tc command: {args:?}", Env::SYS_NAME);
+
+        let output = Command::new("sudo")
+            .arg("tc")
+            .args(args)
+            .output()
+            .expect("sc2-exp(netem): failed to spawn tc command");
+
+        match output.status.code() {
+            Some(0) => String::from_utf8_lossy(&output.stdout).to_string(),
+            Some(code) => {
+                let stderr = str::from_utf8(&output.stderr)
+                    .unwrap_or("sc2-exp(netem): failed to get stderr");
+                panic!(
+                    "{}(netem): tc exited with error (code: {code}): {stderr}",
+                    Env::SYS_NAME
+                );
+            }
+            None => {
+                let stderr = str::from_utf8(&output.stderr)
+                    .unwrap_or("sc2-exp(netem): failed to get stderr");
+                panic!("{}(netem): tc command failed: {stderr}", Env::SYS_NAME);
+            }
+        }
+    }
+
+    /// Get the kind and handle of the root qdisc of a network interface
+    /// (e.g. `("fq_codel", "0:")`). The kernel's default qdiscs have no
+    /// handle (i.e. `0:`), whereas the ones that someone configured do
+    fn get_root_qdisc(iface: &str) -> (String, String) {
+        let stdout = Self::run_tc_cmd(&["qdisc", "show", "dev", iface, "root"]);
+
+        // Each line is `qdisc <kind> <handle> root ...`
+        let mut tokens = stdout.split_whitespace().skip(1);
+        match (tokens.next(), tokens.next()) {
+            (Some(kind), Some(handle)) => (kind.to_string(), handle.to_string()),
+            _ => panic!(
+                "{}(netem): failed to parse root qdisc of {iface}: {stdout}",
+                Env::SYS_NAME
+            ),
+        }
+    }
+
+    /// Resolve the IPv4 address of the container registry we pull images from
+    fn get_registry_ip() -> String {
+        let registry_host = Env::container_registry_url()
+            .split('/')
+            .next()
+            .expect("sc2-exp(netem): failed to get registry host");
+
+        (registry_host, 443)
+            .to_socket_addrs()
+            .expect("sc2-exp(netem): failed to resolve registry host")
+            .find(|addr| addr.is_ipv4())
+            .map(|addr| addr.ip().to_string())
+            .unwrap_or_else(|| {
+                panic!(
+                    "{}(netem): no IPv4 address for registry host: {registry_host}",
+                    Env::SYS_NAME
+                )
+            })
+    }
+
+    /// Work-out the network interface that routes traffic to the given IP
+    fn get_iface_for_ip(ip: &str) -> String {
+        let output = Command::new("ip")
+            .args(["route", "get", ip])
+            .output()
+            .expect("sc2-exp(netem): failed to spawn ip route command");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut tokens = stdout.split_whitespace();
+        while let Some(token) = tokens.next() {
+            if token == "dev" {
+                if let Some(iface) = tokens.next() {
+                    return iface.to_string();
+                }
+            }
+        }
+
+        panic!(
+            "{}(netem): failed to find route to {ip}: {stdout}",
+            Env::SYS_NAME
+        );
+    }
+
+    /// Inject an artificial delay to all traffic going to the container
+    /// registry. We use a `prio` qdisc with an extra band, that only the
+    /// registry-bound traffic is classified to, and attach `netem` to it, so
+    /// that the rest of the traffic (e.g. the Kubernetes control plane) is
+    /// unaffected. As we replace the root qdisc, and delete it afterwards,
+    /// we refuse to run if someone has already configured one
+    pub fn add_registry_delay(delay_ms: u32) {
+        let registry_ip = Self::get_registry_ip();
+        let iface = Self::get_iface_for_ip(&registry_ip);
+
+        let (kind, handle) = Self::get_root_qdisc(&iface);
+        if handle != "0:" {
+            panic!(
+                "{}(netem): {iface} already has a non-default root qdisc ({kind} {handle}), remove it before injecting a registry delay",
+                Env::SYS_NAME
+            );
+        }
+
+        debug!(
+            "{}(netem): adding {delay_ms} ms delay to traffic to {registry_ip} (dev: {iface})",
+            Env::SYS_NAME
+        );
+
+        Self::run_tc_cmd(&[
+            "qdisc", "replace", "dev", &iface, "root", "handle", "1:", "prio", "bands", "4",
+        ]);
+        Self::run_tc_cmd(&[
+            "qdisc",
+            "add",
+            "dev",
+            &iface,
+            "parent",
+            "1:4",
+            "handle",
+            "40:",
+            "netem",
+            "delay",
+            &format!("{delay_ms}ms"),
+        ]);
+        Self::run_tc_cmd(&[
+            "filter",
+            "add",
+            "dev",
+            &iface,
+            "protocol",
+            "ip",
+            "parent",
+            "1:0",
+            "prio",
+            "4",
+            "u32",
+            "match",
+            "ip",
+            "dst",
+            &format!("{registry_ip}/32"),
+            "flowid",
+            "1:4",
+        ]);
+    }
+
+    /// Remove the delay injected with `add_registry_delay`, restoring the
+    /// default root qdisc. We only delete the root qdisc if it is ours
+    pub fn remove_registry_delay() {
+        let registry_ip = Self::get_registry_ip();
+        let iface = Self::get_iface_for_ip(&registry_ip);
+        debug!(
+            "{}(netem): removing delay to traffic to {registry_ip} (dev: {iface})",
+            Env::SYS_NAME
+        );
+
+        let (kind, handle) = Self::get_root_qdisc(&iface);
+        if (kind.as_str(), handle.as_str()) != ("prio", "1:") {
+            warn!(
+                "{}(netem): not removing root qdisc of {iface} ({kind} {handle}) as we did not add it",
+                Env::SYS_NAME
+            );
+            return;
+        }
+
+        Self::run_tc_cmd(&["qdisc", "del", "dev", &iface, "root"]);
+    }
+}
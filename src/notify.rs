@@ -0,0 +1,108 @@
+use crate::env::Env;
+use clap::ValueEnum;
+use log::{debug, warn};
+use serde_json::{json, Value};
+use std::{fmt, process::Command, str};
+
+/// Shape of the payload that we POST to the webhook. Chat webhooks only
+/// show a message from a given field (`text` for Slack, `content` for
+/// Discord), so, for them, we render the summary as a message
+#[derive(Clone, Debug, ValueEnum)]
+pub enum NotifyFormat {
+    Json,
+    Slack,
+    Discord,
+}
+
+impl fmt::Display for NotifyFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifyFormat::Json => write!(f, "json"),
+            NotifyFormat::Slack => write!(f, "slack"),
+            NotifyFormat::Discord => write!(f, "discord"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Notify {}
+
+impl Notify {
+    /// Render a summary as a human-readable message, with one line for
+    /// each of its fields
+    fn summary_to_text(summary: &Value) -> String {
+        let mut text = format!("{}: experiment finished", Env::SYS_NAME);
+        if let Some(fields) = summary.as_object() {
+            for (key, value) in fields {
+                // Do not quote strings
+                let value = value
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| value.to_string());
+                text.push_str(&format!("\n{key}: {value}"));
+            }
+        }
+
+        text
+    }
+
+    /// POST a JSON summary to a webhook (e.g. a Slack or Discord incoming
+    /// webhook). We only warn if we fail to notify, as we do not want to
+    /// mask the outcome of the experiment that we are notifying about
+    pub fn post_webhook(url: &str, format: &NotifyFormat, summary: &Value) {
+        debug!(
+            "{}(notify): posting summary to webhook: {url} (format: {format})",
+            Env::SYS_NAME
+        );
+
+        let payload = match format {
+            NotifyFormat::Json => summary.clone(),
+            NotifyFormat::Slack => json!({ "text": Self::summary_to_text(summary) }),
+            NotifyFormat::Discord => json!({ "content": Self::summary_to_text(summary) }),
+        };
+
+        let output = match Command::new("curl")
+            .args([
+                "--silent",
+                "--show-error",
+                "--fail",
+                "--max-time",
+                "30",
+                "-X",
+                "POST",
+                "-H",
+                "Content-Type: application/json",
+                "--data",
+                &payload.to_string(),
+                url,
+            ])
+            .output()
+        {
+            Ok(output) => output,
+            Err(e) => {
+                warn!(
+                    "{}(notify): failed to spawn curl command: {e}",
+                    Env::SYS_NAME
+                );
+                return;
+            }
+        };
+
+        match output.status.code() {
+            Some(0) => {}
+            Some(code) => {
+                let stderr = str::from_utf8(&output.stderr)
+                    .unwrap_or("sc2-exp(notify): failed to get stderr");
+                warn!(
+                    "{}(notify): curl exited with error (code: {code}): {stderr}",
+                    Env::SYS_NAME
+                );
+            }
+            None => {
+                let stderr = str::from_utf8(&output.stderr)
+                    .unwrap_or("sc2-exp(notify): failed to get stderr");
+                warn!("{}(notify): curl command failed: {stderr}", Env::SYS_NAME);
+            }
+        };
+    }
+}
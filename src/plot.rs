@@ -1,40 +1,2573 @@
 use crate::{
-    containerd::Containerd,
+    containerd::ContainerdEvent,
     env::Env,
     experiment::{AvailableBaselines, AvailableExperiments},
 };
+use clap::{Args, ValueEnum};
 use csv::ReaderBuilder;
-use log::debug;
-use plotters::prelude::*;
-use serde::Deserialize;
-use std::{collections::BTreeMap, fs, path::PathBuf};
+use log::{debug, warn};
+use plotters::{
+    coord::{types::RangedCoordf64, Shift},
+    prelude::*,
+};
+use regex::Regex;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    io::Write,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum PlotFormat {
+    Png,
+    Svg,
+}
+
+impl fmt::Display for PlotFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlotFormat::Png => write!(f, "png"),
+            PlotFormat::Svg => write!(f, "svg"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum PlotStatistic {
+    Mean,
+    Median,
+    P95,
+    P99,
+}
+
+impl fmt::Display for PlotStatistic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlotStatistic::Mean => write!(f, "mean"),
+            PlotStatistic::Median => write!(f, "median"),
+            PlotStatistic::P95 => write!(f, "p95"),
+            PlotStatistic::P99 => write!(f, "p99"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum PlotKind {
+    Bars,
+    Cdf,
+    Variance,
+}
+
+impl fmt::Display for PlotKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlotKind::Bars => write!(f, "bars"),
+            PlotKind::Cdf => write!(f, "cdf"),
+            PlotKind::Variance => write!(f, "variance"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum PlotMissing {
+    Omit,
+    Placeholder,
+}
+
+impl fmt::Display for PlotMissing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlotMissing::Omit => write!(f, "omit"),
+            PlotMissing::Placeholder => write!(f, "placeholder"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, ValueEnum)]
+pub enum PlotStyle {
+    Stacked,
+    Grouped,
+}
+
+impl fmt::Display for PlotStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlotStyle::Stacked => write!(f, "stacked"),
+            PlotStyle::Grouped => write!(f, "grouped"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum PlotPhase {
+    Measured,
+    Warmup,
+    All,
+}
+
+impl fmt::Display for PlotPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlotPhase::Measured => write!(f, "measured"),
+            PlotPhase::Warmup => write!(f, "warmup"),
+            PlotPhase::All => write!(f, "all"),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct PlotArgs {
+    #[arg(long, value_enum, default_value_t = PlotFormat::Svg)]
+    format: PlotFormat,
+    #[arg(long, default_value = "600")]
+    width: u32,
+    #[arg(long, default_value = "400")]
+    height: u32,
+    #[arg(long, value_enum, default_value_t = PlotStatistic::Mean)]
+    statistic: PlotStatistic,
+    #[arg(long, value_enum, default_value_t = PlotKind::Bars)]
+    kind: PlotKind,
+    #[arg(long, value_enum, default_value_t = PlotStyle::Stacked)]
+    style: PlotStyle,
+    #[arg(long)]
+    normalize: bool,
+    #[arg(long, value_enum, default_value_t = PlotMissing::Placeholder)]
+    missing: PlotMissing,
+    #[arg(long, num_args = 1.., value_name = "BASELINE")]
+    baseline: Vec<AvailableBaselines>,
+    #[arg(long)]
+    show_points: bool,
+    #[arg(long, visible_alias = "log-y")]
+    log_scale: bool,
+    #[arg(long)]
+    group_sc2: bool,
+    #[arg(long, value_name = "MS", conflicts_with = "normalize")]
+    sla_ms: Option<f64>,
+    #[arg(long)]
+    title: Option<String>,
+    #[arg(long, value_name = "GLOB")]
+    filter: Option<String>,
+    #[arg(long, value_enum, default_value_t = PlotPhase::Measured)]
+    phase: PlotPhase,
+    #[arg(long, value_name = "RESULTS_DIR")]
+    compare_dir: Option<PathBuf>,
+}
+
+// Time spent in each event (or end-to-end) for each baseline
+type BaselineEventTimes = BTreeMap<AvailableBaselines, BTreeMap<ContainerdEvent, f64>>;
+type BaselineTimes = BTreeMap<AvailableBaselines, f64>;
+// Time spent in each event in each run, for each sweep, and for each baseline
+type SweepSamples = BTreeMap<String, BTreeMap<ContainerdEvent, Vec<f64>>>;
+type BaselineSweepSamples = BTreeMap<AvailableBaselines, SweepSamples>;
+
+/// Aggregated data for the start-up bar plot, in the units that we plot
+struct StartUpPlotData {
+    cold_data: BTreeMap<AvailableBaselines, BTreeMap<ContainerdEvent, f64>>,
+    warm_data: BTreeMap<AvailableBaselines, BTreeMap<ContainerdEvent, f64>>,
+    cold_stddev: BTreeMap<AvailableBaselines, f64>,
+    warm_stddev: BTreeMap<AvailableBaselines, f64>,
+    // Baselines with no data for each flavour, that we draw a placeholder for
+    cold_missing: BTreeSet<AvailableBaselines>,
+    warm_missing: BTreeSet<AvailableBaselines>,
+    // End-to-end time of each individual run, only if we draw them
+    cold_points: BTreeMap<AvailableBaselines, Vec<f64>>,
+    warm_points: BTreeMap<AvailableBaselines, Vec<f64>>,
+    // With a log-scale y axis, the bars start at y_min instead of zero
+    log_scale: bool,
+    // Draw one bar per event, side by side, instead of stacking them. We
+    // always do so with a log-scale y axis
+    grouped: bool,
+    y_min: f64,
+    y_max: f64,
+    y_label: &'static str,
+    // Label each baseline and its SC2 variant as a pair
+    group_sc2: bool,
+    // Target latency (in ms) that we draw a reference line at
+    sla_ms: Option<f64>,
+    // Relative change (in %) of the end-to-end time with respect to the
+    // results in another directory, that we annotate each bar with
+    cold_change: BaselineTimes,
+    warm_change: BaselineTimes,
+}
 
 #[derive(Debug)]
 pub struct Plot {}
 
 impl Plot {
-    /// Collect all CSV files in the data directory for the experiment
-    fn get_all_data_files(exp: &AvailableExperiments) -> Vec<PathBuf> {
-        let mut data_path = Env::results_root();
-        data_path.push(format!("{exp}"));
-        data_path.push("data");
+    /// Reference chart size that all the hand-placed coordinates (labels,
+    /// legends, ...) are expressed in
+    const CHART_SIZE_PX: (u32, u32) = (600, 400);
+
+    /// Height (in the reference chart size) of the x-axis labels when we
+    /// group the SC2 variants, that leaves room for the brackets below them
+    const GROUPED_LABEL_Y_PX: i32 = 330;
+
+    /// Height (in pixels) of each row of the start-up legend
+    const LEGEND_ROW_HEIGHT_PX: i32 = 28;
+
+    /// Events that we stack in the start-up bar plots, in the order that we
+    /// stack them in. We draw the orchestration time as the StartUp segment
+    fn start_up_stacked_events() -> impl Iterator<Item = &'static ContainerdEvent> {
+        ContainerdEvent::iter_variants()
+            .chain([ContainerdEvent::GuestLazyPull].iter())
+            .map(|event| match event {
+                ContainerdEvent::StartUp => &ContainerdEvent::Orchestration,
+                _ => event,
+            })
+    }
+
+    /// Time spent in the events under each entry of the start-up legend. When
+    /// we do not stack the events, we draw one bar for each of them
+    fn start_up_group_times(
+        times: &BTreeMap<ContainerdEvent, f64>,
+        legend: &[(&'static str, RGBColor)],
+    ) -> Vec<f64> {
+        legend
+            .iter()
+            .map(|(label, _)| {
+                Self::start_up_stacked_events()
+                    .filter(|event| event.legend_label() == *label)
+                    .map(|event| times[event])
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Whether to plot a baseline, given the (optional) list of baselines to
+    /// restrict the plot to
+    fn is_baseline_selected(args: &PlotArgs, baseline: &AvailableBaselines) -> bool {
+        args.baseline.is_empty() || args.baseline.contains(baseline)
+    }
+
+    /// Whether to plot a data file, which we name after the baseline that
+    /// generated it (i.e. `<baseline>_<...>.csv`), and, if given a --filter,
+    /// whose file name matches the (glob) filter
+    fn is_data_file_selected(args: &PlotArgs, data_file: &Path) -> bool {
+        if let Some(filter) = &args.filter {
+            let filter_re = Regex::new(&format!(
+                "^{}$",
+                regex::escape(filter)
+                    .replace(r"\*", ".*")
+                    .replace(r"\?", ".")
+            ))
+            .unwrap();
+            let file_name = data_file
+                .file_name()
+                .map(|f| f.to_string_lossy())
+                .unwrap_or_default();
+            if !filter_re.is_match(&file_name) {
+                return false;
+            }
+        }
+
+        let baseline = data_file
+            .file_stem()
+            .and_then(|f| f.to_str())
+            .and_then(|f| f.split('_').next())
+            .and_then(|b| b.parse::<AvailableBaselines>().ok());
+
+        match baseline {
+            Some(baseline) => Self::is_baseline_selected(args, &baseline),
+            None => true,
+        }
+    }
+
+    /// Whether a path is a data file. Data files are either CSV files or
+    /// JSON Lines files. If a run wrote both, we only read the CSV one
+    fn is_data_file(path: &Path) -> bool {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => true,
+            Some("jsonl") => !path.with_extension("csv").exists(),
+            _ => false,
+        }
+    }
+
+    /// Whether we plot a record in the given phase (i.e. warm-up or measured
+    /// run). Records from before we recorded the phase are all measured
+    fn is_phase_selected(args: &PlotArgs, phase: Option<&str>) -> bool {
+        let phase = phase.unwrap_or("measured");
+        match args.phase {
+            PlotPhase::All => true,
+            _ => phase == args.phase.to_string(),
+        }
+    }
+
+    /// Read all the records in a data file, either in CSV or in JSON Lines
+    /// format, that are in the phase that we plot. Records are named after
+    /// the CSV headers (in PascalCase), so we convert the JSON keys (in
+    /// snake_case) accordingly
+    fn read_data_file<T: DeserializeOwned>(args: &PlotArgs, data_file: &Path) -> Vec<T> {
+        if data_file.extension().and_then(|e| e.to_str()) == Some("jsonl") {
+            return fs::read_to_string(data_file)
+                .unwrap()
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| serde_json::from_str::<Value>(line).unwrap())
+                .filter(|json| {
+                    Self::is_phase_selected(args, json.get("phase").and_then(Value::as_str))
+                })
+                .map(|json| {
+                    let record: Map<String, Value> = json
+                        .as_object()
+                        .unwrap()
+                        .iter()
+                        .map(|(key, value)| {
+                            let key: String = key
+                                .split('_')
+                                .map(|word| {
+                                    let mut chars = word.chars();
+                                    match chars.next() {
+                                        Some(first) => first.to_uppercase().chain(chars).collect(),
+                                        None => String::new(),
+                                    }
+                                })
+                                .collect();
+                            (key, value.clone())
+                        })
+                        .collect();
+                    serde_json::from_value(Value::Object(record)).unwrap()
+                })
+                .collect();
+        }
+
+        let mut reader = ReaderBuilder::new()
+            .has_headers(true)
+            .from_path(data_file)
+            .unwrap();
+        let headers = reader.headers().unwrap().clone();
+        let phase_idx = headers.iter().position(|header| header == "Phase");
+        reader
+            .records()
+            .map(|result| result.unwrap())
+            .filter(|record| {
+                Self::is_phase_selected(args, phase_idx.and_then(|idx| record.get(idx)))
+            })
+            .map(|record| record.deserialize(Some(&headers)).unwrap())
+            .collect()
+    }
+
+    /// Collect the data files in a directory that we should plot, and, if
+    /// recursive, in all of its sub-directories
+    fn find_data_files(dir: &Path, args: &PlotArgs, recursive: bool) -> Vec<PathBuf> {
+        let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        entries.sort();
+
+        let mut data_files = Vec::new();
+        for path in entries {
+            if path.is_dir() {
+                if recursive {
+                    data_files.extend(Self::find_data_files(&path, args, recursive));
+                }
+            } else if Self::is_data_file(&path) && Self::is_data_file_selected(args, &path) {
+                data_files.push(path);
+            }
+        }
+
+        data_files
+    }
+
+    /// Warn about data files with the same name (i.e. the same baseline and
+    /// configuration) in different sub-directories, as we merge their samples
+    /// into the same data point
+    fn warn_on_duplicate_data_files(data_files: &[PathBuf]) {
+        let mut dirs_by_name = BTreeMap::<String, Vec<String>>::new();
+        for data_file in data_files {
+            dirs_by_name
+                .entry(data_file.file_name().unwrap().to_string_lossy().to_string())
+                .or_default()
+                .push(
+                    data_file
+                        .parent()
+                        .map(|dir| dir.display().to_string())
+                        .unwrap_or_default(),
+                );
+        }
+
+        for (name, dirs) in dirs_by_name.iter().filter(|(_, dirs)| dirs.len() > 1) {
+            warn!(
+                "{}(plot): merging the samples of {name} from: {}",
+                Env::SYS_NAME,
+                dirs.join(", ")
+            );
+        }
+    }
+
+    /// Get the data directory for the experiment under a results directory
+    /// (e.g. the one that we are plotting, or one that we compare against)
+    fn get_data_path(results_root: &Path, exp: &AvailableExperiments) -> PathBuf {
+        results_root.join(format!("{exp}")).join("data")
+    }
+
+    /// Collect all data files in the data directory for the experiment,
+    /// including those in sub-directories (e.g. one per machine, or per
+    /// date). For the start-up experiment, each sub-directory holds a
+    /// different sweep, so we only collect the top-level files here, and
+    /// collect the sweeps separately
+    fn get_all_data_files(
+        data_path: &Path,
+        exp: &AvailableExperiments,
+        args: &PlotArgs,
+    ) -> Vec<PathBuf> {
+        Self::find_data_files(data_path, args, *exp != AvailableExperiments::StartUp)
+    }
+
+    /// Collect the CSV files for each sweep of the experiment. A sweep is a
+    /// sub-directory of the data directory (e.g. `data/<sweep>/*.csv`) that
+    /// holds the results of a full run of the experiment from a fresh
+    /// cluster state (in any of its sub-directories)
+    fn get_sweep_data_files(data_path: &Path, args: &PlotArgs) -> BTreeMap<String, Vec<PathBuf>> {
+        let mut sweeps = BTreeMap::<String, Vec<PathBuf>>::new();
+        for entry in fs::read_dir(data_path).unwrap() {
+            let sweep_path = entry.unwrap().path();
+            if !sweep_path.is_dir() {
+                continue;
+            }
+
+            let csv_files = Self::find_data_files(&sweep_path, args, true);
+            if !csv_files.is_empty() {
+                sweeps.insert(
+                    sweep_path
+                        .file_name()
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string(),
+                    csv_files,
+                );
+            }
+        }
+
+        sweeps
+    }
+
+    /// Get the path to write a plot to, with the extension that corresponds
+    /// to the requested output format
+    fn get_plot_path(exp: &AvailableExperiments, suffix: &str, format: &PlotFormat) -> PathBuf {
+        let mut plot_path = Env::results_root();
+        plot_path.push(format!("{exp}"));
+        plot_path.push("plots");
+        fs::create_dir_all(plot_path.clone()).unwrap();
+        plot_path.push(format!(
+            "{}{suffix}.{format}",
+            exp.to_string().replace("-", "_")
+        ));
+        plot_path
+    }
+
+    /// Write the aggregated time spent in each event (in ms), for each
+    /// baseline and flavour, as a CSV file next to the plots, so that we can
+    /// put the numbers behind a plot in a table. We only have an error for
+    /// the end-to-end time, so we leave it empty for the other events
+    fn write_start_up_summary(
+        exp: &AvailableExperiments,
+        args: &PlotArgs,
+        flavours: [(
+            &str,
+            &BaselineEventTimes,
+            &BaselineTimes,
+            &BTreeSet<AvailableBaselines>,
+        ); 2],
+    ) {
+        let mut summary_path = Env::results_root();
+        summary_path.push(format!("{exp}"));
+        summary_path.push("plots");
+        fs::create_dir_all(&summary_path).unwrap();
+        summary_path.push("summary.csv");
+
+        let mut file = fs::File::create(&summary_path).unwrap_or_else(|e| {
+            panic!(
+                "{}(plot): failed to create summary at {}: {e}",
+                Env::SYS_NAME,
+                summary_path.display()
+            )
+        });
+        writeln!(file, "Baseline,Flavour,Event,Statistic,TimeMs,StddevMs").unwrap();
+        for (flavour, data, stddev, baselines) in flavours {
+            for (baseline, times) in data
+                .iter()
+                .filter(|(baseline, _)| baselines.contains(*baseline))
+            {
+                for (event, time_ms) in times {
+                    let stddev_ms = match event {
+                        ContainerdEvent::StartUp => format!("{:.2}", stddev[baseline]),
+                        _ => String::new(),
+                    };
+                    writeln!(
+                        file,
+                        "{baseline},{flavour},{event},{},{time_ms:.2},{stddev_ms}",
+                        args.statistic
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        println!(
+            "{}(plot): generated summary at: {}",
+            Env::SYS_NAME,
+            summary_path.display()
+        );
+    }
+
+    /// Draw the plot's title at the top of the drawing area, and return the
+    /// area below it to draw the chart on. A --title replaces the title of
+    /// the primary plot only (e.g. not the containerd CPU one next to it),
+    /// unless it has a `{plot}` placeholder for what each plot shows
+    fn draw_title<DB: DrawingBackend>(
+        root: DrawingArea<DB, Shift>,
+        args: &PlotArgs,
+        exp: &AvailableExperiments,
+        plot_name: &str,
+        is_primary: bool,
+    ) -> DrawingArea<DB, Shift> {
+        root.fill(&WHITE).unwrap();
+
+        let title = match &args.title {
+            Some(title) if title.contains("{plot}") => title.replace("{plot}", plot_name),
+            Some(title) if is_primary => title.clone(),
+            _ => format!("{exp}: {plot_name}"),
+        };
+        let font = ("sans-serif", 20).into_font().color(&BLACK);
+        let (title_width_px, title_height_px) = root.estimate_text_size(&title, &font).unwrap();
+        let (width_px, _) = root.dim_in_pixel();
+        root.draw(&Text::new(
+            title,
+            ((width_px as i32 - title_width_px as i32) / 2, 6),
+            font,
+        ))
+        .unwrap();
+
+        let (_, chart_area) = root.split_vertically(title_height_px + 12);
+        chart_area
+    }
+
+    /// Turn a position in the backend's pixels (e.g. a chart's backend
+    /// coordinates) into a position relative to the drawing area, which may
+    /// not start at the top of the backend (e.g. below the title)
+    fn to_area_px<DB: DrawingBackend>(
+        root: &DrawingArea<DB, Shift>,
+        pos: (i32, i32),
+    ) -> (i32, i32) {
+        let (base_x, base_y) = root.get_base_pixel();
+        (pos.0 - base_x, pos.1 - base_y)
+    }
+
+    /// Scale a position, given in pixels for the reference chart size, to
+    /// the actual size of the drawing area
+    fn scale_pos_px<DB: DrawingBackend>(
+        root: &DrawingArea<DB, Shift>,
+        pos: (i32, i32),
+    ) -> (i32, i32) {
+        let (width, height) = root.dim_in_pixel();
+        (
+            pos.0 * width as i32 / Self::CHART_SIZE_PX.0 as i32,
+            pos.1 * height as i32 / Self::CHART_SIZE_PX.1 as i32,
+        )
+    }
+
+    fn mean(samples: &[f64]) -> f64 {
+        if samples.is_empty() {
+            return 0.0;
+        }
+
+        samples.iter().sum::<f64>() / samples.len() as f64
+    }
+
+    /// Percentile (in 0..100) using linear interpolation between the closest
+    /// ranks
+    fn percentile(samples: &[f64], pct: f64) -> f64 {
+        if samples.is_empty() {
+            return 0.0;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let rank = pct / 100.0 * (sorted.len() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+    }
+
+    /// Collapse all the per-run samples into one value using the requested
+    /// statistic
+    fn aggregate(samples: &[f64], statistic: &PlotStatistic) -> f64 {
+        match statistic {
+            PlotStatistic::Mean => Self::mean(samples),
+            PlotStatistic::Median => Self::percentile(samples, 50.0),
+            PlotStatistic::P95 => Self::percentile(samples, 95.0),
+            PlotStatistic::P99 => Self::percentile(samples, 99.0),
+        }
+    }
+
+    /// Population standard deviation
+    fn stddev(samples: &[f64]) -> f64 {
+        if samples.is_empty() {
+            return 0.0;
+        }
+
+        let mean = Self::mean(samples);
+        let variance =
+            samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        variance.sqrt()
+    }
+
+    /// Standard error of the mean, using the sample standard deviation
+    fn standard_error(samples: &[f64]) -> f64 {
+        if samples.len() < 2 {
+            return 0.0;
+        }
+
+        let mean = Self::mean(samples);
+        let variance =
+            samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (samples.len() - 1) as f64;
+        (variance / samples.len() as f64).sqrt()
+    }
+
+    /// Read the per-run metrics that were recorded alongside a data file.
+    /// Metrics live in a file with the same name as the data file, in a
+    /// sibling `metrics` directory. Returns an empty map if there are none
+    fn get_metrics_for_data_file(data_file: &Path) -> BTreeMap<u32, BTreeMap<String, u64>> {
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            run: u32,
+            metric: String,
+            value: u64,
+        }
+
+        let mut metrics = BTreeMap::<u32, BTreeMap<String, u64>>::new();
+        let metrics_file = match (
+            data_file.parent().and_then(|p| p.parent()),
+            data_file.file_name(),
+        ) {
+            (Some(exp_dir), Some(file_name)) => exp_dir.join("metrics").join(file_name),
+            _ => return metrics,
+        };
+        if !metrics_file.exists() {
+            return metrics;
+        }
+
+        let mut reader = ReaderBuilder::new()
+            .has_headers(true)
+            .from_path(&metrics_file)
+            .unwrap();
+        for result in reader.deserialize() {
+            let record: Record = result.unwrap();
+            metrics
+                .entry(record.run)
+                .or_default()
+                .insert(record.metric, record.value);
+        }
+
+        metrics
+    }
+
+    /// Plot the CPU time consumed by containerd during each run against the
+    /// image pull latency (in the host plus in the guest) for the same run
+    fn plot_containerd_cpu(exp: &AvailableExperiments, args: &PlotArgs, data_files: &Vec<PathBuf>) {
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            run: u32,
+            event: String,
+            time_ms: u64,
+        }
+
+        // ---------- Collect Data ---------- //
+
+        // For each baseline and flavour, keep a list of (pull latency,
+        // containerd CPU time) points, one per run, both in seconds
+        let mut data = BTreeMap::<(AvailableBaselines, String), Vec<(f64, f64)>>::new();
+
+        let mut x_max: f64 = 0.0;
+        let mut y_max: f64 = 0.0;
+        for csv_file in data_files {
+            let metrics = Self::get_metrics_for_data_file(csv_file);
+            if metrics.is_empty() {
+                continue;
+            }
+
+            let file_name_no_ext = csv_file
+                .file_stem()
+                .and_then(|f| f.to_str())
+                .unwrap_or_default();
+            let baseline: AvailableBaselines = file_name_no_ext.split('_').collect::<Vec<_>>()[0]
+                .parse()
+                .unwrap();
+            let flavour: String = file_name_no_ext.split('_').collect::<Vec<_>>()[1]
+                .parse()
+                .unwrap();
+
+            let mut pull_times = BTreeMap::<u32, f64>::new();
+            for record in Self::read_data_file::<Record>(args, csv_file) {
+                if matches!(
+                    record.event.parse(),
+                    Ok(ContainerdEvent::PullImage | ContainerdEvent::StartContainerUserContainer)
+                ) {
+                    *pull_times.entry(record.run).or_insert(0.0) += record.time_ms as f64;
+                }
+            }
+
+            let points = data.entry((baseline, flavour)).or_default();
+            for (run, pull_time) in pull_times {
+                if let Some(cpu_ms) = metrics.get(&run).and_then(|m| m.get("ContainerdCpuMs")) {
+                    let point = (pull_time / 1000.0, *cpu_ms as f64 / 1000.0);
+                    x_max = x_max.max(point.0);
+                    y_max = y_max.max(point.1);
+                    points.push(point);
+                }
+            }
+        }
+
+        if data.is_empty() {
+            debug!("{}(plot): no containerd metrics to plot", Env::SYS_NAME);
+            return;
+        }
+
+        // ---------- Plot Data ---------- //
+
+        let plot_path = Self::get_plot_path(exp, "_containerd_cpu", &args.format);
+        match args.format {
+            PlotFormat::Png => Self::draw_containerd_cpu(
+                Self::draw_title(
+                    BitMapBackend::new(&plot_path, (args.width, args.height)).into_drawing_area(),
+                    args,
+                    exp,
+                    "containerd CPU",
+                    false,
+                ),
+                &plot_path,
+                &data,
+                x_max,
+                y_max,
+            ),
+            PlotFormat::Svg => Self::draw_containerd_cpu(
+                Self::draw_title(
+                    SVGBackend::new(&plot_path, (args.width, args.height)).into_drawing_area(),
+                    args,
+                    exp,
+                    "containerd CPU",
+                    false,
+                ),
+                &plot_path,
+                &data,
+                x_max,
+                y_max,
+            ),
+        }
+    }
+
+    fn draw_containerd_cpu<DB: DrawingBackend>(
+        root: DrawingArea<DB, Shift>,
+        plot_path: &Path,
+        data: &BTreeMap<(AvailableBaselines, String), Vec<(f64, f64)>>,
+        x_max: f64,
+        y_max: f64,
+    ) {
+        root.fill(&WHITE).unwrap();
+
+        let x_max = x_max.ceil().max(1.0);
+        let y_max = y_max.ceil().max(1.0);
+        let legend = Self::baselines_legend(data.keys().map(|(baseline, _)| baseline));
+        let mut chart = ChartBuilder::on(&root)
+            .x_label_area_size(40)
+            .y_label_area_size(40)
+            .margin(10)
+            .margin_top(Self::legend_margin_px(&root, &legend, 0))
+            .build_cartesian_2d(0.0..x_max, 0f64..y_max)
+            .unwrap();
+
+        chart
+            .configure_mesh()
+            .x_label_style(("sans-serif", 20).into_font())
+            .y_label_style(("sans-serif", 20).into_font())
+            .x_labels(5)
+            .y_labels(5)
+            .disable_x_mesh()
+            .x_label_formatter(&|x| format!("{:.0}", x))
+            .y_label_formatter(&|y| format!("{:.1}", y))
+            .draw()
+            .unwrap();
+
+        // Manually draw the axis labels with a custom font and size
+        root.draw(&Text::new(
+            "containerd CPU Time [s]",
+            Self::scale_pos_px(&root, (3, 290)),
+            ("sans-serif", 20)
+                .into_font()
+                .transform(FontTransform::Rotate270)
+                .color(&BLACK),
+        ))
+        .unwrap();
+        root.draw(&Text::new(
+            "Image Pull Latency [s]",
+            Self::scale_pos_px(&root, (200, 375)),
+            ("sans-serif", 20).into_font().color(&BLACK),
+        ))
+        .unwrap();
+
+        // Cold runs are drawn as filled circles, warm runs as hollow ones
+        for ((baseline, flavour), points) in data.iter() {
+            let style = if flavour == "cold" {
+                baseline.get_color().filled()
+            } else {
+                baseline.get_color().stroke_width(2)
+            };
+            chart
+                .draw_series(points.iter().map(|(x, y)| Circle::new((*x, *y), 4, style)))
+                .unwrap();
+        }
+
+        Self::draw_legend(&root, &legend);
+
+        println!(
+            "{}(plot): generated plot at: {}",
+            Env::SYS_NAME,
+            plot_path.display()
+        );
+        root.present().unwrap();
+    }
+
+    /// Read the time spent in each event in each run of the start-up
+    /// experiment, for each baseline and sweep, split by flavour (cold and
+    /// warm). Not all runs report all events, so missing events count as zero
+    fn read_start_up_samples(
+        args: &PlotArgs,
+        sweeps: &BTreeMap<String, Vec<PathBuf>>,
+    ) -> (BaselineSweepSamples, BaselineSweepSamples) {
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            run: u32,
+            event: String,
+            time_ms: u64,
+        }
+
+        let mut cold_samples = BaselineSweepSamples::new();
+        let mut warm_samples = BaselineSweepSamples::new();
+        for (sweep, csv_file) in sweeps
+            .iter()
+            .flat_map(|(sweep, files)| files.iter().map(move |file| (sweep, file)))
+        {
+            let file_name_no_ext = csv_file
+                .file_stem()
+                .and_then(|f| f.to_str())
+                .unwrap_or_default();
+            let baseline: AvailableBaselines = file_name_no_ext.split('_').collect::<Vec<_>>()[0]
+                .parse()
+                .unwrap();
+            let flavour: String = file_name_no_ext.split('_').collect::<Vec<_>>()[1]
+                .parse()
+                .unwrap();
+
+            // Based on the flavour, we pick one of the data dictionaries
+            let samples = match flavour.as_str() {
+                "cold" => &mut cold_samples,
+                "warm" => &mut warm_samples,
+                _ => panic!("unreachable"),
+            };
+
+            debug!("Reading data for baseline: {baseline}/{flavour} (file: {csv_file:?}");
+
+            // Open the CSV and deserialize records, grouping them by run
+            let mut runs = BTreeMap::<u32, BTreeMap<ContainerdEvent, f64>>::new();
+            for record in Self::read_data_file::<Record>(args, csv_file) {
+                if let Ok(event) = record.event.parse() {
+                    runs.entry(record.run)
+                        .or_default()
+                        .insert(event, record.time_ms as f64);
+                }
+            }
+
+            let sweep_samples = samples
+                .entry(baseline)
+                .or_default()
+                .entry(sweep.clone())
+                .or_default();
+            for event in
+                ContainerdEvent::iter_variants().chain(ContainerdEvent::iter_snapshotter_variants())
+            {
+                sweep_samples.insert(
+                    *event,
+                    runs.values()
+                        .map(|run| *run.get(event).unwrap_or(&0.0))
+                        .collect(),
+                );
+            }
+        } // End processing one CSV file
+
+        (cold_samples, warm_samples)
+    }
+
+    /// Aggregate the end-to-end start-up time of a baseline, and its error.
+    /// With a single sweep, the error is the standard deviation across runs;
+    /// with multiple sweeps, we first aggregate each sweep, and use the
+    /// standard error across sweeps
+    fn aggregate_start_up(baseline_sweeps: &SweepSamples, statistic: &PlotStatistic) -> (f64, f64) {
+        if baseline_sweeps.len() == 1 {
+            let start_up_samples = baseline_sweeps
+                .values()
+                .next()
+                .unwrap()
+                .get(&ContainerdEvent::StartUp)
+                .unwrap();
+            (
+                Self::aggregate(start_up_samples, statistic),
+                Self::stddev(start_up_samples),
+            )
+        } else {
+            let start_up_sweep_avgs: Vec<f64> = baseline_sweeps
+                .values()
+                .map(|sweep_samples| {
+                    Self::aggregate(
+                        sweep_samples.get(&ContainerdEvent::StartUp).unwrap(),
+                        statistic,
+                    )
+                })
+                .collect();
+            (
+                Self::mean(&start_up_sweep_avgs),
+                Self::standard_error(&start_up_sweep_avgs),
+            )
+        }
+    }
+
+    /// Get the relative change (in %) of the end-to-end start-up time of
+    /// each baseline with respect to the results in another directory, for
+    /// the baselines that have data in both
+    fn get_start_up_change(
+        args: &PlotArgs,
+        exp: &AvailableExperiments,
+        compare_dir: &Path,
+        cold_samples: &BaselineSweepSamples,
+        warm_samples: &BaselineSweepSamples,
+    ) -> (BaselineTimes, BaselineTimes) {
+        let data_path = Self::get_data_path(compare_dir, exp);
+        if !data_path.is_dir() {
+            panic!(
+                "{}(plot): no {exp} data to compare against at: {}",
+                Env::SYS_NAME,
+                data_path.display()
+            );
+        }
+
+        let mut sweeps = Self::get_sweep_data_files(&data_path, args);
+        let data_files = Self::get_all_data_files(&data_path, exp, args);
+        if !data_files.is_empty() {
+            sweeps.insert(String::new(), data_files);
+        }
+        let (old_cold_samples, old_warm_samples) = Self::read_start_up_samples(args, &sweeps);
+
+        let get_change = |samples: &BaselineSweepSamples, old_samples: &BaselineSweepSamples| {
+            samples
+                .iter()
+                .filter_map(|(baseline, baseline_sweeps)| {
+                    let (old_start_up, _) =
+                        Self::aggregate_start_up(old_samples.get(baseline)?, &args.statistic);
+                    let (start_up, _) = Self::aggregate_start_up(baseline_sweeps, &args.statistic);
+                    (old_start_up > 0.0).then(|| {
+                        (
+                            baseline.clone(),
+                            100.0 * (start_up - old_start_up) / old_start_up,
+                        )
+                    })
+                })
+                .collect::<BaselineTimes>()
+        };
+
+        let cold_change = get_change(cold_samples, &old_cold_samples);
+        let warm_change = get_change(warm_samples, &old_warm_samples);
+        for (flavour, change) in [("cold", &cold_change), ("warm", &warm_change)] {
+            for (baseline, change) in change {
+                println!(
+                    "{}(plot): {baseline}/{flavour}: {change:+.1}% vs {}",
+                    Env::SYS_NAME,
+                    compare_dir.display()
+                );
+            }
+        }
+
+        (cold_change, warm_change)
+    }
+
+    fn plot_start_up_latency(exp: &AvailableExperiments, args: &PlotArgs, data_files: &[PathBuf]) {
+        // ---------- Collect Data ---------- //
+
+        // This map has one key per baseline, and each baseline holds a map
+        // of each sweep, and for each sweep, the time spent in each event in
+        // each run
+        let mut sweeps =
+            Self::get_sweep_data_files(&Self::get_data_path(&Env::results_root(), exp), args);
+        if !data_files.is_empty() {
+            sweeps.insert(String::new(), data_files.to_vec());
+        }
+        let (cold_samples, warm_samples) = Self::read_start_up_samples(args, &sweeps);
+
+        // Optionally, compare against the results in another directory (e.g.
+        // before an optimization)
+        let (cold_change, warm_change) = match &args.compare_dir {
+            Some(compare_dir) => {
+                Self::get_start_up_change(args, exp, compare_dir, &cold_samples, &warm_samples)
+            }
+            None => Default::default(),
+        };
+
+        // This map has one key per baseline, and each baseline holds a map
+        // of each event and the average time spent in each event (or the
+        // statistic requested with --statistic).
+        // Note: we stack averages together, which may not be the most
+        // statistically-wise thing. To give an idea of the spread, we also
+        // keep an error for the end-to-end time: with a single sweep, this is
+        // the standard deviation across runs; with multiple sweeps, we first
+        // average each sweep, and use the standard error across sweeps
+        let mut cold_data = BTreeMap::<AvailableBaselines, BTreeMap<ContainerdEvent, f64>>::new();
+        let mut warm_data = BTreeMap::<AvailableBaselines, BTreeMap<ContainerdEvent, f64>>::new();
+        let mut cold_stddev = BTreeMap::<AvailableBaselines, f64>::new();
+        let mut warm_stddev = BTreeMap::<AvailableBaselines, f64>::new();
+
+        let mut y_max: f64 = 25.0e3;
+        for (samples, data, stddev) in [
+            (&cold_samples, &mut cold_data, &mut cold_stddev),
+            (&warm_samples, &mut warm_data, &mut warm_stddev),
+        ] {
+            for baseline in AvailableBaselines::iter_variants() {
+                let mut inner_map = BTreeMap::<ContainerdEvent, f64>::new();
+                for event in ContainerdEvent::iter_variants()
+                    .chain(ContainerdEvent::iter_snapshotter_variants())
+                {
+                    inner_map.insert(*event, 0.0);
+                }
+                inner_map.insert(ContainerdEvent::Orchestration, 0.0);
+                data.insert(baseline.clone(), inner_map);
+                stddev.insert(baseline.clone(), 0.0);
+
+                let Some(baseline_sweeps) = samples.get(baseline) else {
+                    continue;
+                };
+
+                // Calculate the average (across sweeps) of the per-sweep
+                // statistic (the mean, by default)
+                let mut orchestration_time = 0.0;
+                for event in ContainerdEvent::iter_variants()
+                    .chain(ContainerdEvent::iter_snapshotter_variants())
+                {
+                    let sweep_avgs: Vec<f64> = baseline_sweeps
+                        .values()
+                        .map(|sweep_samples| {
+                            Self::aggregate(sweep_samples.get(event).unwrap(), &args.statistic)
+                        })
+                        .collect();
+                    let avg = Self::mean(&sweep_avgs);
+                    data.get_mut(baseline).unwrap().insert(*event, avg);
+
+                    // Snapshotter events, and events with a parent event,
+                    // overlap with other containerd events, so we must not
+                    // account for them twice
+                    if event.is_sequential() {
+                        orchestration_time += avg;
+                    }
+                }
+
+                let (start_up_avg, start_up_stddev) =
+                    Self::aggregate_start_up(baseline_sweeps, &args.statistic);
+                stddev.insert(baseline.clone(), start_up_stddev);
+
+                // Keep track of the highest bar (including its error bar)
+                if start_up_avg + start_up_stddev > y_max {
+                    y_max = start_up_avg + start_up_stddev;
+                }
+
+                // Add an additional event corresponding to "Orchestration" which
+                // we define as StartUp - sum(AllOtherEvents)
+                orchestration_time = start_up_avg - orchestration_time;
+                data.get_mut(baseline)
+                    .unwrap()
+                    .insert(ContainerdEvent::Orchestration, orchestration_time);
+
+                // Events that happen while their parent event is in progress
+                // (e.g. the lazy pull in the guest happens while starting the
+                // user container) we stack by carving them out of the parent
+                let times = data.get_mut(baseline).unwrap();
+                for event in ContainerdEvent::iter_variants()
+                    .chain(ContainerdEvent::iter_snapshotter_variants())
+                {
+                    if let Some(parent) = event.parent_event() {
+                        let time = times[event].min(times[&parent]);
+                        times.insert(*event, time);
+                        *times.get_mut(&parent).unwrap() -= time;
+                    }
+                }
+            }
+        }
+
+        // ---------- Plot Data ---------- //
+
+        for flavour in ["cold", "warm"] {
+            let data = match flavour {
+                "cold" => cold_data.clone(),
+                "warm" => warm_data.clone(),
+                _ => panic!("unreachable"),
+            };
+
+            for (baseline, times) in data.iter() {
+                for (event, avg) in times.iter() {
+                    debug!("{baseline}/{flavour}/{event}: {avg} ms");
+                }
+            }
+        }
+
+        // Write the numbers behind the plot (before converting them to the
+        // units that we plot) for the baselines that we have data for
+        let with_data = |samples: &BaselineSweepSamples| {
+            samples
+                .keys()
+                .filter(|baseline| Self::is_baseline_selected(args, baseline))
+                .cloned()
+                .collect::<BTreeSet<_>>()
+        };
+        Self::write_start_up_summary(
+            exp,
+            args,
+            [
+                ("cold", &cold_data, &cold_stddev, &with_data(&cold_samples)),
+                ("warm", &warm_data, &warm_stddev, &with_data(&warm_samples)),
+            ],
+        );
+
+        // Convert all times to the units that we plot: either seconds, or,
+        // if requested, multiples of the runc start-up time for the same
+        // flavour, so that we can read the overheads off the plot
+        let (y_label, cold_unit, warm_unit) = if args.normalize {
+            let get_runc_start_up = |data: &BTreeMap<
+                AvailableBaselines,
+                BTreeMap<ContainerdEvent, f64>,
+            >,
+                                     samples: &BaselineSweepSamples,
+                                     flavour: &str| {
+                if samples.is_empty() {
+                    return 1.0;
+                }
+
+                let runc_start_up = match samples.contains_key(&AvailableBaselines::Runc) {
+                    true => data[&AvailableBaselines::Runc][&ContainerdEvent::StartUp],
+                    false => 0.0,
+                };
+                if runc_start_up == 0.0 {
+                    panic!(
+                        "{}(plot): cannot normalize to runc: no {flavour} data for runc (expected: {}_{flavour}.csv)",
+                        Env::SYS_NAME,
+                        AvailableBaselines::Runc
+                    );
+                }
+
+                runc_start_up
+            };
+
+            (
+                "Start-Up Latency [normalized to runc]",
+                get_runc_start_up(&cold_data, &cold_samples, "cold"),
+                get_runc_start_up(&warm_data, &warm_samples, "warm"),
+            )
+        } else {
+            ("Start-Up Latency [s]", 1000.0, 1000.0)
+        };
+
+        if args.normalize {
+            y_max = 0.0;
+            for (data, stddev, unit) in [
+                (&cold_data, &cold_stddev, cold_unit),
+                (&warm_data, &warm_stddev, warm_unit),
+            ] {
+                for (baseline, times) in data.iter() {
+                    y_max = y_max.max((times[&ContainerdEvent::StartUp] + stddev[baseline]) / unit);
+                }
+            }
+        } else {
+            y_max /= 1000.0;
+        }
+
+        for (data, stddev, unit) in [
+            (&mut cold_data, &mut cold_stddev, cold_unit),
+            (&mut warm_data, &mut warm_stddev, warm_unit),
+        ] {
+            for times in data.values_mut() {
+                for time in times.values_mut() {
+                    *time /= unit;
+                }
+            }
+            for time in stddev.values_mut() {
+                *time /= unit;
+            }
+        }
+
+        // Baselines that we have not run would show as an instant start-up,
+        // so we either omit them from the plot altogether, or draw a
+        // placeholder instead of their bars
+        let mut cold_missing = BTreeSet::<AvailableBaselines>::new();
+        let mut warm_missing = BTreeSet::<AvailableBaselines>::new();
+        for baseline in AvailableBaselines::iter_variants() {
+            if !Self::is_baseline_selected(args, baseline) {
+                cold_data.remove(baseline);
+                warm_data.remove(baseline);
+                cold_stddev.remove(baseline);
+                warm_stddev.remove(baseline);
+                continue;
+            }
+
+            let has_cold = cold_samples.contains_key(baseline);
+            let has_warm = warm_samples.contains_key(baseline);
+
+            match args.missing {
+                PlotMissing::Omit if !has_cold && !has_warm => {
+                    debug!(
+                        "{}(plot): omitting baseline with no data: {baseline}",
+                        Env::SYS_NAME
+                    );
+                    cold_data.remove(baseline);
+                    warm_data.remove(baseline);
+                    cold_stddev.remove(baseline);
+                    warm_stddev.remove(baseline);
+                }
+                PlotMissing::Omit => {}
+                PlotMissing::Placeholder => {
+                    if !has_cold {
+                        cold_missing.insert(baseline.clone());
+                    }
+                    if !has_warm {
+                        warm_missing.insert(baseline.clone());
+                    }
+                }
+            }
+        }
+
+        // Optionally, keep the end-to-end time of each run (across all
+        // sweeps) to draw it on top of the bars
+        let mut cold_points = BTreeMap::<AvailableBaselines, Vec<f64>>::new();
+        let mut warm_points = BTreeMap::<AvailableBaselines, Vec<f64>>::new();
+        if args.show_points {
+            for (samples, data, points, unit) in [
+                (&cold_samples, &cold_data, &mut cold_points, cold_unit),
+                (&warm_samples, &warm_data, &mut warm_points, warm_unit),
+            ] {
+                for (baseline, baseline_sweeps) in samples {
+                    if !data.contains_key(baseline) {
+                        continue;
+                    }
+
+                    let baseline_points: Vec<f64> = baseline_sweeps
+                        .values()
+                        .flat_map(|sweep_samples| sweep_samples[&ContainerdEvent::StartUp].iter())
+                        .map(|time| time / unit)
+                        .collect();
+                    for point in &baseline_points {
+                        y_max = y_max.max(*point);
+                    }
+                    points.insert(baseline.clone(), baseline_points);
+                }
+            }
+        }
+
+        // Make sure that the target latency line fits in the plot
+        if let Some(sla_ms) = args.sla_ms {
+            y_max = y_max.max(sla_ms / 1000.0 * 1.1);
+        }
+
+        // Leave room for the relative change on top of the highest bars
+        if args.compare_dir.is_some() {
+            y_max *= if args.log_scale { 2.0 } else { 1.1 };
+        }
+
+        // A log-scale axis can not start at zero, so we start it at the
+        // power of ten right below (half) the shortest bar (including the
+        // per-event bars), or data point
+        let y_min = if args.log_scale {
+            let legend = Self::start_up_latency_legend(&cold_data, &warm_data);
+            let min_y = [&cold_data, &warm_data]
+                .iter()
+                .flat_map(|data| data.values())
+                .flat_map(|times| {
+                    Self::start_up_group_times(times, &legend)
+                        .into_iter()
+                        .chain([times[&ContainerdEvent::StartUp]])
+                })
+                .chain(
+                    cold_points
+                        .values()
+                        .chain(warm_points.values())
+                        .flatten()
+                        .cloned(),
+                )
+                .filter(|y| *y > 0.0)
+                .fold(f64::INFINITY, f64::min);
+
+            if min_y.is_finite() {
+                10f64.powf((min_y / 2.0).log10().floor())
+            } else {
+                y_max / 10.0
+            }
+        } else {
+            0.0
+        };
+
+        let plot_data = StartUpPlotData {
+            cold_data,
+            warm_data,
+            cold_stddev,
+            warm_stddev,
+            cold_missing,
+            warm_missing,
+            cold_points,
+            warm_points,
+            log_scale: args.log_scale,
+            grouped: args.log_scale || args.style == PlotStyle::Grouped,
+            y_min,
+            y_max,
+            y_label,
+            group_sc2: args.group_sc2,
+            sla_ms: args.sla_ms,
+            cold_change,
+            warm_change,
+        };
+
+        let plot_path = Self::get_plot_path(exp, "", &args.format);
+        match args.format {
+            PlotFormat::Png => Self::draw_start_up_latency(
+                Self::draw_title(
+                    BitMapBackend::new(&plot_path, (args.width, args.height)).into_drawing_area(),
+                    args,
+                    exp,
+                    "latency",
+                    true,
+                ),
+                &plot_path,
+                &plot_data,
+            ),
+            PlotFormat::Svg => Self::draw_start_up_latency(
+                Self::draw_title(
+                    SVGBackend::new(&plot_path, (args.width, args.height)).into_drawing_area(),
+                    args,
+                    exp,
+                    "latency",
+                    true,
+                ),
+                &plot_path,
+                &plot_data,
+            ),
+        }
+    }
+
+    /// Legend entries (label and colour) for the events that we stack in
+    /// the start-up bar plots, in the order that we stack them in. Events
+    /// that share a colour share an entry, and we leave out the events that
+    /// no baseline spends any time in
+    fn start_up_legend<'a>(
+        data: &[&BaselineEventTimes],
+        events: impl Iterator<Item = &'a ContainerdEvent>,
+    ) -> Vec<(&'static str, RGBColor)> {
+        let mut legend: Vec<(&'static str, RGBColor)> = Vec::new();
+        for event in events {
+            let is_drawn = data
+                .iter()
+                .flat_map(|data| data.values())
+                .any(|times| times.get(event).copied().unwrap_or(0.0) > 0.0);
+            if is_drawn
+                && !legend
+                    .iter()
+                    .any(|(label, _)| *label == event.legend_label())
+            {
+                legend.push((event.legend_label(), event.color()));
+            }
+        }
+
+        legend
+    }
+
+    /// Legend entries for the start-up latency plot, whether we stack the
+    /// events or draw one bar for each legend entry
+    fn start_up_latency_legend(
+        cold_data: &BaselineEventTimes,
+        warm_data: &BaselineEventTimes,
+    ) -> Vec<(&'static str, RGBColor)> {
+        Self::start_up_legend(&[cold_data, warm_data], Self::start_up_stacked_events())
+    }
+
+    /// Lay out the legend entries left to right, above the chart, wrapping to
+    /// a new row when we run out of width. Returns the position of each entry
+    fn layout_legend<DB: DrawingBackend, L: AsRef<str>>(
+        root: &DrawingArea<DB, Shift>,
+        legend: &[(L, RGBColor)],
+    ) -> Vec<(i32, i32)> {
+        let font = ("sans-serif", 20).into_font().color(&BLACK);
+        let (x_start, y_start) = Self::scale_pos_px(root, (20, 6));
+        let (width_px, _) = root.dim_in_pixel();
+
+        let (mut x_pos, mut y_pos) = (x_start, y_start);
+        let mut positions = Vec::new();
+        for (label, _) in legend {
+            let (label_width_px, _) = root.estimate_text_size(label.as_ref(), &font).unwrap();
+            let entry_width_px = 30 + label_width_px as i32 + 15;
+            if x_pos > x_start && x_pos + entry_width_px > width_px as i32 {
+                x_pos = x_start;
+                y_pos += Self::LEGEND_ROW_HEIGHT_PX;
+            }
+            positions.push((x_pos, y_pos));
+            x_pos += entry_width_px;
+        }
+
+        positions
+    }
+
+    /// Top margin of a chart, that leaves room for the legend above it, plus
+    /// any number of extra rows
+    fn legend_margin_px<DB: DrawingBackend, L: AsRef<str>>(
+        root: &DrawingArea<DB, Shift>,
+        legend: &[(L, RGBColor)],
+        num_extra_rows: u32,
+    ) -> u32 {
+        let num_rows = Self::layout_legend(root, legend)
+            .iter()
+            .map(|(_, y_pos)| y_pos)
+            .collect::<BTreeSet<_>>()
+            .len()
+            .max(1) as u32;
+
+        40 + (num_rows - 1 + num_extra_rows) * Self::LEGEND_ROW_HEIGHT_PX as u32
+    }
+
+    /// Draw the x-axis labels (one per plotted baseline) and the legend (one
+    /// entry per colour that we draw) shared by the start-up bar plots. The
+    /// x-axis labels are centred under each baseline's group of bars, given
+    /// the pixel range that the plotting area spans in the x axis. If we
+    /// group the SC2 variants, we label each baseline that we plot next to
+    /// its SC2 variant as "base" and "sc2", and draw a bracket under both
+    /// with the baseline's name
+    fn draw_start_up_labels<DB: DrawingBackend>(
+        root: &DrawingArea<DB, Shift>,
+        x_range_px: Range<i32>,
+        baselines: &[&AvailableBaselines],
+        legend: &[(&'static str, RGBColor)],
+        group_sc2: bool,
+    ) {
+        // Manually draw the x-axis labels with a custom font and size
+        let slot_width_px = (x_range_px.end - x_range_px.start) as f64 / baselines.len() as f64;
+        let x_mid_for_idx =
+            |idx: usize| x_range_px.start + ((idx as f64 + 0.5) * slot_width_px) as i32;
+        let (_, y_pos) = Self::scale_pos_px(
+            root,
+            (
+                0,
+                if group_sc2 {
+                    Self::GROUPED_LABEL_Y_PX
+                } else {
+                    360
+                },
+            ),
+        );
+        let is_paired_with_next = |idx: usize| {
+            group_sc2
+                && baselines
+                    .get(idx + 1)
+                    .is_some_and(|next| baselines[idx].sc2_variant().as_ref() == Some(*next))
+        };
+        for (idx, baseline) in baselines.iter().enumerate() {
+            let label = if is_paired_with_next(idx) {
+                "base".to_string()
+            } else if idx > 0 && is_paired_with_next(idx - 1) {
+                "sc2".to_string()
+            } else {
+                format!("{baseline}")
+            };
+            let font = ("sans-serif", 20).into_font().color(&BLACK);
+            let (label_width_px, _) = root.estimate_text_size(&label, &font).unwrap();
+            let x_mid = x_mid_for_idx(idx);
+
+            root.draw(&Text::new(
+                label,
+                (x_mid - label_width_px as i32 / 2, y_pos),
+                font.clone(),
+            ))
+            .unwrap();
+
+            if !is_paired_with_next(idx) {
+                continue;
+            }
+
+            // Draw a bracket under the pair, with the baseline's name
+            let (_, bracket_y_pos) = Self::scale_pos_px(root, (0, Self::GROUPED_LABEL_Y_PX + 24));
+            let (_, tick_y_pos) = Self::scale_pos_px(root, (0, Self::GROUPED_LABEL_Y_PX + 20));
+            let bracket_start = x_mid - slot_width_px as i32 / 3;
+            let bracket_end = x_mid_for_idx(idx + 1) + slot_width_px as i32 / 3;
+            root.draw(&PathElement::new(
+                vec![
+                    (bracket_start, tick_y_pos),
+                    (bracket_start, bracket_y_pos),
+                    (bracket_end, bracket_y_pos),
+                    (bracket_end, tick_y_pos),
+                ],
+                BLACK,
+            ))
+            .unwrap();
+
+            let group_label = format!("{baseline}");
+            let (group_label_width_px, _) = root.estimate_text_size(&group_label, &font).unwrap();
+            root.draw(&Text::new(
+                group_label,
+                (
+                    (bracket_start + bracket_end - group_label_width_px as i32) / 2,
+                    bracket_y_pos + 4,
+                ),
+                font,
+            ))
+            .unwrap();
+        }
+
+        Self::draw_legend(root, legend);
+    }
+
+    /// Manually draw the legend outside the grid, above the chart, as laid
+    /// out by `layout_legend`
+    fn draw_legend<DB: DrawingBackend, L: AsRef<str>>(
+        root: &DrawingArea<DB, Shift>,
+        legend: &[(L, RGBColor)],
+    ) {
+        for ((label, color), (x_pos, y_pos)) in legend.iter().zip(Self::layout_legend(root, legend))
+        {
+            // Draw the color box (Rectangle)
+            root.draw(&Rectangle::new(
+                [(x_pos, y_pos), (x_pos + 20, y_pos + 20)],
+                color.filled(),
+            ))
+            .unwrap();
+
+            // Draw the label (Text)
+            root.draw(&Text::new(
+                label.as_ref(),
+                (x_pos + 30, y_pos + 5),
+                ("sans-serif", 20).into_font(),
+            ))
+            .unwrap();
+        }
+    }
+
+    /// Legend with one entry (in its color) for each baseline that we plot
+    fn baselines_legend<'a>(
+        baselines: impl Iterator<Item = &'a AvailableBaselines>,
+    ) -> Vec<(String, RGBColor)> {
+        let mut baselines: Vec<&AvailableBaselines> = baselines.collect();
+        baselines.dedup();
+        baselines
+            .into_iter()
+            .map(|baseline| (format!("{baseline}"), baseline.get_color()))
+            .collect()
+    }
+
+    fn draw_start_up_latency<DB: DrawingBackend>(
+        root: DrawingArea<DB, Shift>,
+        plot_path: &Path,
+        plot_data: &StartUpPlotData,
+    ) {
+        let (y_min, y_max, y_label) = (plot_data.y_min, plot_data.y_max, plot_data.y_label);
+
+        root.fill(&WHITE).unwrap();
+
+        // When we do not stack the events, we add a note under the legend
+        let legend = Self::start_up_latency_legend(&plot_data.cold_data, &plot_data.warm_data);
+        let margin_top_px =
+            Self::legend_margin_px(&root, &legend, if plot_data.grouped { 1 } else { 0 });
+
+        let x_max = plot_data.cold_data.len() as f64;
+        let mut chart_builder = ChartBuilder::on(&root);
+        chart_builder
+            .x_label_area_size(if plot_data.group_sc2 { 70 } else { 40 })
+            .y_label_area_size(40)
+            .margin(10)
+            .margin_top(margin_top_px);
+
+        if plot_data.log_scale {
+            let mut chart = chart_builder
+                .build_cartesian_2d(0.0..x_max, (y_min..y_max).log_scale())
+                .unwrap();
+
+            chart
+                .configure_mesh()
+                .y_label_style(("sans-serif", 20).into_font())
+                .y_labels(10)
+                .disable_x_mesh()
+                .disable_x_axis()
+                .y_label_formatter(&|y| {
+                    if *y < 1.0 {
+                        format!("{}", y)
+                    } else {
+                        format!("{:.0}", y)
+                    }
+                })
+                .draw()
+                .unwrap();
+
+            Self::draw_start_up_bars(&root, chart, plot_data);
+        } else {
+            let mut chart = chart_builder
+                .build_cartesian_2d(0.0..x_max, y_min..y_max)
+                .unwrap();
+
+            chart
+                .configure_mesh()
+                .y_label_style(("sans-serif", 20).into_font())
+                .y_labels(10)
+                .y_max_light_lines(5)
+                .disable_x_mesh()
+                .disable_x_axis()
+                .y_label_formatter(&|y| {
+                    if y_max > 5.0 {
+                        format!("{:.0}", y)
+                    } else {
+                        format!("{:.1}", y)
+                    }
+                })
+                .draw()
+                .unwrap();
+
+            Self::draw_start_up_bars(&root, chart, plot_data);
+        }
+
+        // Manually draw the y-axis label with a custom font and size. The
+        // label is rotated, so make sure that long labels still fit
+        let (y_label_len, _) = root
+            .estimate_text_size(y_label, &("sans-serif", 20).into())
+            .unwrap();
+        let (y_label_x, y_label_y) = Self::scale_pos_px(&root, (3, 280));
+        root.draw(&Text::new(
+            y_label,
+            (y_label_x, y_label_y.max(y_label_len as i32 + 10)),
+            ("sans-serif", 20)
+                .into_font()
+                .transform(FontTransform::Rotate270)
+                .color(&BLACK),
+        ))
+        .unwrap();
+
+        println!(
+            "{}(plot): generated plot at: {}",
+            Env::SYS_NAME,
+            plot_path.display()
+        );
+        root.present().unwrap();
+    }
+
+    /// Draw the start-up bars (and everything on top of them) on a chart
+    /// with either a linear or a log-scale y axis. Stacked bars do not
+    /// compose on a log-scale axis, as the height of each segment would
+    /// depend on the segments below it, so, in that case, we draw one bar for
+    /// each group of events in the legend, side by side, inside a frame with
+    /// the end-to-end time
+    fn draw_start_up_bars<DB: DrawingBackend, Y: Ranged<ValueType = f64>>(
+        root: &DrawingArea<DB, Shift>,
+        mut chart: ChartContext<DB, Cartesian2d<RangedCoordf64, Y>>,
+        plot_data: &StartUpPlotData,
+    ) {
+        let StartUpPlotData {
+            cold_data,
+            warm_data,
+            cold_stddev,
+            warm_stddev,
+            cold_missing,
+            warm_missing,
+            cold_points,
+            warm_points,
+            log_scale,
+            grouped,
+            y_min,
+            y_max,
+            y_label: _,
+            group_sc2,
+            sla_ms,
+            cold_change,
+            warm_change,
+        } = plot_data;
+        let (log_scale, grouped, y_min, y_max) = (*log_scale, *grouped, *y_min, *y_max);
+        let legend = Self::start_up_latency_legend(cold_data, warm_data);
+
+        // Height, in data units, of a given fraction of the y axis
+        let y_at = |fraction: f64| {
+            if log_scale {
+                y_min * (y_max / y_min).powf(fraction)
+            } else {
+                y_min + fraction * (y_max - y_min)
+            }
+        };
+
+        // The bar frames' margin is computed for the reference chart width,
+        // so scale it with the requested width
+        let (chart_width_px, _) = Self::scale_pos_px(root, (400, 0));
+        let x_max = cold_data.len() as f64;
+
+        let bar_width = 0.5;
+        for (data_idx, (data, stddev)) in (0..).zip([
+            (cold_data.clone(), &cold_stddev),
+            (warm_data.clone(), &warm_stddev),
+        ]) {
+            // Draw bars: we draw one series for each event, and we stack them
+            // together
+            let mut prev_y_map: BTreeMap<&AvailableBaselines, f64> = BTreeMap::new();
+            for baseline in AvailableBaselines::iter_variants() {
+                prev_y_map.insert(baseline, y_min);
+            }
+
+            if grouped {
+                let num_groups = legend.len().max(1);
+                let group_width = bar_width / num_groups as f64;
+                chart
+                    .draw_series(
+                        (0..)
+                            .zip(data.iter())
+                            .flat_map(|(x, (baseline, event_vec))| {
+                                prev_y_map.insert(
+                                    baseline,
+                                    event_vec[&ContainerdEvent::StartUp].max(y_min),
+                                );
+
+                                let x_orig: f64 = x as f64 + 0.5 * data_idx as f64;
+                                legend
+                                    .iter()
+                                    .zip(Self::start_up_group_times(event_vec, &legend))
+                                    .enumerate()
+                                    .map(move |(group_idx, ((_, color), group_y))| {
+                                        let this_color = if data_idx == 0 {
+                                            (*color).into()
+                                        } else {
+                                            color.mix(0.6)
+                                        };
+                                        let bar_style = ShapeStyle {
+                                            color: this_color,
+                                            filled: true,
+                                            stroke_width: 2,
+                                        };
+
+                                        let group_x = x_orig + group_width * group_idx as f64;
+                                        let mut bar = Rectangle::new(
+                                            [
+                                                (group_x, y_min),
+                                                (group_x + group_width, group_y.max(y_min)),
+                                            ],
+                                            bar_style,
+                                        );
+                                        bar.set_margin(0, 0, 1, 1);
+
+                                        bar
+                                    })
+                            }),
+                    )
+                    .unwrap();
+            }
+
+            for event in ContainerdEvent::iter_variants()
+                .chain([ContainerdEvent::GuestLazyPull].iter())
+                .filter(|_| !grouped)
+            {
+                chart
+                    .draw_series((0..).zip(data.iter()).map(|(x, (baseline, event_vec))| {
+                        let this_color = if data_idx == 0 {
+                            event.color().into()
+                        } else {
+                            event.color().mix(0.6)
+                        };
+                        let bar_style = ShapeStyle {
+                            color: this_color,
+                            filled: true,
+                            stroke_width: 2,
+                        };
+
+                        // Handle the StartUp case separately
+                        let mut this_y = *event_vec.get(event).unwrap();
+                        if *event == ContainerdEvent::StartUp {
+                            this_y = *event_vec.get(&ContainerdEvent::Orchestration).unwrap();
+                        }
+                        let prev_y = prev_y_map.get_mut(baseline).unwrap();
+
+                        let x_orig: f64 = x as f64 + 0.5 * data_idx as f64;
+
+                        let mut bar = Rectangle::new(
+                            [(x_orig, *prev_y), (x_orig + bar_width, *prev_y + this_y)],
+                            bar_style,
+                        );
+                        *prev_y += this_y;
+
+                        // Set the margins so that bars for the same baseline
+                        // touch
+                        if data_idx == 0 {
+                            bar.set_margin(0, 0, 2, 0);
+                        } else {
+                            bar.set_margin(0, 0, 0, 2);
+                        }
+
+                        bar
+                    }))
+                    .unwrap();
+            }
+
+            // Add black frame around each bar
+            chart
+                .draw_series((0..).zip(data.iter()).map(|(x, (baseline, _))| {
+                    // Benefit from the fact that prev_y stores the maximum y
+                    // value after we plot the stacked bar chart
+                    let this_y = *prev_y_map.get_mut(baseline).unwrap();
+
+                    let x_orig: f64 = x as f64 + 0.5 * data_idx as f64;
+                    let margin_px = 2;
+                    let x_axis_range = 0.0..x_max;
+                    let margin_units = margin_px as f64 * (x_axis_range.end - x_axis_range.start)
+                        / chart_width_px as f64;
+
+                    if data_idx == 0 {
+                        PathElement::new(
+                            vec![
+                                (x_orig + margin_units, this_y),
+                                (x_orig + bar_width, this_y),
+                                (x_orig + bar_width, y_min),
+                                (x_orig + margin_units, y_min),
+                                (x_orig + margin_units, this_y),
+                            ],
+                            BLACK,
+                        )
+                    } else {
+                        PathElement::new(
+                            vec![
+                                (x_orig, this_y),
+                                (x_orig - margin_units + bar_width, this_y),
+                                (x_orig - margin_units + bar_width, y_min),
+                                (x_orig, y_min),
+                                (x_orig, this_y),
+                            ],
+                            BLACK,
+                        )
+                    }
+                }))
+                .unwrap();
+
+            // Add error bars with the standard deviation of the end-to-end
+            // time, centered on the top of each bar
+            chart
+                .draw_series((0..).zip(data.iter()).map(|(x, (baseline, _))| {
+                    let this_y = *prev_y_map.get(baseline).unwrap();
+                    let this_stddev = *stddev.get(baseline).unwrap();
+
+                    let x_mid: f64 = x as f64 + 0.5 * data_idx as f64 + bar_width / 2.0;
+                    let cap_width = bar_width / 6.0;
+                    let y_low = (this_y - this_stddev).max(y_min);
+                    let y_high = this_y + this_stddev;
+
+                    PathElement::new(
+                        vec![
+                            (x_mid - cap_width, y_low),
+                            (x_mid + cap_width, y_low),
+                            (x_mid, y_low),
+                            (x_mid, y_high),
+                            (x_mid - cap_width, y_high),
+                            (x_mid + cap_width, y_high),
+                        ],
+                        BLACK,
+                    )
+                }))
+                .unwrap();
+        }
+
+        // Draw the end-to-end time of each run as a small circle on top of
+        // its bar. We spread the circles along the bar's width (with a
+        // deterministic jitter) so that they do not overlap
+        for (data_idx, points) in (0..).zip([cold_points, warm_points]) {
+            for (x, baseline) in (0..).zip(cold_data.keys()) {
+                let Some(baseline_points) = points.get(baseline) else {
+                    continue;
+                };
+
+                let x_orig: f64 = x as f64 + 0.5 * data_idx as f64;
+                chart
+                    .draw_series((0..).zip(baseline_points.iter()).map(|(idx, point)| {
+                        let jitter = (idx as f64 * 0.618_034) % 1.0;
+                        let x_pos = x_orig + bar_width * (0.2 + 0.6 * jitter);
+                        Circle::new((x_pos, *point), 3, RGBColor(64, 64, 64).filled())
+                    }))
+                    .unwrap();
+            }
+        }
+
+        // Draw a hatched placeholder, with a "no data" label, in place of
+        // the bars for which we have no data
+        let placeholder_height = y_at(0.25);
+        for (data_idx, missing) in (0..).zip([cold_missing, warm_missing]) {
+            for (x, _) in (0..)
+                .zip(cold_data.keys())
+                .filter(|(_, baseline)| missing.contains(baseline))
+            {
+                let x_orig: f64 = x as f64 + 0.5 * data_idx as f64;
+                let num_hatches = 8;
+                chart
+                    .draw_series((0..num_hatches).map(|idx| {
+                        let y_low = y_at(0.25 * idx as f64 / num_hatches as f64);
+                        let y_high = y_at(0.25 * (idx + 1) as f64 / num_hatches as f64);
+                        PathElement::new(
+                            vec![(x_orig, y_low), (x_orig + bar_width, y_high)],
+                            BLACK.mix(0.4),
+                        )
+                    }))
+                    .unwrap();
+                chart
+                    .draw_series(std::iter::once(PathElement::new(
+                        vec![
+                            (x_orig, placeholder_height),
+                            (x_orig + bar_width, placeholder_height),
+                            (x_orig + bar_width, y_min),
+                            (x_orig, y_min),
+                            (x_orig, placeholder_height),
+                        ],
+                        BLACK.mix(0.4),
+                    )))
+                    .unwrap();
+
+                let (x_px, y_px) = Self::to_area_px(
+                    root,
+                    chart.backend_coord(&(x_orig + bar_width / 2.0, y_min)),
+                );
+                root.draw(&Text::new(
+                    "no data",
+                    (x_px - 6, y_px - 4),
+                    ("sans-serif", 12)
+                        .into_font()
+                        .transform(FontTransform::Rotate270)
+                        .color(&BLACK),
+                ))
+                .unwrap();
+            }
+        }
+
+        // Draw the target latency as a dashed line across the whole plot, on
+        // top of the bars, so that we can tell which baselines meet it
+        if let Some(sla_ms) = sla_ms {
+            let sla_y = sla_ms / 1000.0;
+            let num_dashes = 40;
+            let dash_width = x_max / num_dashes as f64;
+            chart
+                .draw_series((0..num_dashes).map(|idx| {
+                    let x_orig = idx as f64 * dash_width;
+                    PathElement::new(
+                        vec![(x_orig, sla_y), (x_orig + dash_width / 2.0, sla_y)],
+                        RED.stroke_width(2),
+                    )
+                }))
+                .unwrap();
+
+            let label = format!("SLA: {sla_ms} ms");
+            let font = ("sans-serif", 14).into_font().color(&RED);
+            let (label_width_px, label_height_px) = root.estimate_text_size(&label, &font).unwrap();
+            let (x_px, y_px) = Self::to_area_px(root, chart.backend_coord(&(x_max, sla_y)));
+            root.draw(&Text::new(
+                label,
+                (
+                    x_px - label_width_px as i32 - 4,
+                    y_px - label_height_px as i32 - 2,
+                ),
+                font,
+            ))
+            .unwrap();
+        }
+
+        // Add solid frames around grid
+        chart
+            .plotting_area()
+            .draw(&PathElement::new(vec![(0.0, y_max), (x_max, y_max)], BLACK))
+            .unwrap();
+        chart
+            .plotting_area()
+            .draw(&PathElement::new(
+                vec![(x_max, y_min), (x_max, y_max)],
+                BLACK,
+            ))
+            .unwrap();
+        chart
+            .plotting_area()
+            .draw(&PathElement::new(vec![(0.0, y_min), (x_max, y_min)], BLACK))
+            .unwrap();
+
+        Self::draw_start_up_labels(
+            root,
+            chart.plotting_area().get_pixel_range().0,
+            &cold_data.keys().collect::<Vec<_>>(),
+            &legend,
+            *group_sc2,
+        );
+
+        // Make it clear, in the legend, that the bars are not stacked
+        if grouped {
+            let (x_pos, _) = Self::scale_pos_px(root, (20, 0));
+            let y_pos = Self::layout_legend(root, &legend)
+                .iter()
+                .map(|(_, y_pos)| *y_pos)
+                .max()
+                .unwrap_or(Self::scale_pos_px(root, (0, 6)).1)
+                + Self::LEGEND_ROW_HEIGHT_PX;
+            root.draw(&Text::new(
+                if log_scale {
+                    "(log scale: one bar per event, frame: end-to-end)"
+                } else {
+                    "(one bar per event, frame: end-to-end)"
+                },
+                (x_pos, y_pos + 5),
+                ("sans-serif", 16).into_font().style(FontStyle::Italic),
+            ))
+            .unwrap();
+        }
+
+        // Annotate each bar with the relative change of its end-to-end time
+        // with respect to the results that we compare against, in green if
+        // it got faster, and in red if it got slower
+        let change_font = ("sans-serif", 12).into_font();
+        let (_, change_height_px) = root
+            .estimate_text_size("+0%", &change_font.clone().color(&BLACK))
+            .unwrap();
+        for (data_idx, (data, stddev, change)) in (0..).zip([
+            (cold_data, cold_stddev, cold_change),
+            (warm_data, warm_stddev, warm_change),
+        ]) {
+            for (x, (baseline, times)) in (0..).zip(data.iter()) {
+                let Some(change) = change.get(baseline) else {
+                    continue;
+                };
+
+                let label = format!("{change:+.0}%");
+                let color = if change.abs() < 0.5 {
+                    BLACK
+                } else if *change < 0.0 {
+                    RGBColor(0, 128, 0)
+                } else {
+                    RED
+                };
+                let font = change_font.clone().color(&color);
+                let (label_width_px, _) = root.estimate_text_size(&label, &font).unwrap();
+                let bar_top = times[&ContainerdEvent::StartUp] + stddev[baseline];
+                let (x_px, y_px) = Self::to_area_px(
+                    root,
+                    chart.backend_coord(&(
+                        x as f64 + 0.5 * data_idx as f64 + bar_width / 2.0,
+                        bar_top,
+                    )),
+                );
+                root.draw(&Text::new(
+                    label,
+                    (
+                        x_px - label_width_px as i32 / 2,
+                        y_px - change_height_px as i32 - 2,
+                    ),
+                    font,
+                ))
+                .unwrap();
+            }
+        }
+
+        // Manually draw cold/warm labels on top of the bars for the first
+        // baseline (and on top of their relative change, if any)
+        if let Some(baseline) = cold_data.keys().next() {
+            for (data_idx, (label, data, stddev, missing, change)) in (0..).zip([
+                ("cold", cold_data, cold_stddev, cold_missing, cold_change),
+                ("warm", warm_data, warm_stddev, warm_missing, warm_change),
+            ]) {
+                let bar_top = if missing.contains(baseline) {
+                    placeholder_height
+                } else {
+                    data[baseline][&ContainerdEvent::StartUp] + stddev[baseline]
+                };
+
+                let font = ("sans-serif", 14).into_font().color(&BLACK);
+                let (label_width_px, label_height_px) =
+                    root.estimate_text_size(label, &font).unwrap();
+                let (x_px, y_px) = Self::to_area_px(
+                    root,
+                    chart.backend_coord(&(0.5 * data_idx as f64 + bar_width / 2.0, bar_top)),
+                );
+                let change_offset_px = if change.contains_key(baseline) {
+                    change_height_px as i32 + 2
+                } else {
+                    0
+                };
+                root.draw(&Text::new(
+                    label,
+                    (
+                        x_px - label_width_px as i32 / 2,
+                        y_px - label_height_px as i32 - 4 - change_offset_px,
+                    ),
+                    font,
+                ))
+                .unwrap();
+            }
+        }
+    }
+
+    /// Plot, for each baseline, the fraction of the end-to-end start-up
+    /// variance that each event contributes to. Given that the end-to-end time
+    /// is the sum of all events (plus orchestration), we decompose its variance
+    /// as Var(StartUp) = sum_i Cov(Event_i, StartUp)
+    fn plot_start_up_variance(exp: &AvailableExperiments, args: &PlotArgs, data_files: &[PathBuf]) {
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            run: u32,
+            event: String,
+            time_ms: u64,
+        }
+
+        // ---------- Collect Data ---------- //
+
+        // Collect the per-run time spent in each event for each baseline and
+        // flavour. Not all runs report all events, so missing events count as
+        // zero. As in the bar plot, we use the StartUp key to hold the
+        // orchestration time
+        let mut cold_runs =
+            BTreeMap::<AvailableBaselines, Vec<BTreeMap<ContainerdEvent, f64>>>::new();
+        let mut warm_runs =
+            BTreeMap::<AvailableBaselines, Vec<BTreeMap<ContainerdEvent, f64>>>::new();
+        let sweep_files =
+            Self::get_sweep_data_files(&Self::get_data_path(&Env::results_root(), exp), args);
+        for csv_file in data_files.iter().chain(sweep_files.values().flatten()) {
+            let file_name_no_ext = csv_file
+                .file_stem()
+                .and_then(|f| f.to_str())
+                .unwrap_or_default();
+            let baseline: AvailableBaselines = file_name_no_ext.split('_').collect::<Vec<_>>()[0]
+                .parse()
+                .unwrap();
+            let flavour = file_name_no_ext.split('_').collect::<Vec<_>>()[1];
+
+            let runs = match flavour {
+                "cold" => &mut cold_runs,
+                "warm" => &mut warm_runs,
+                _ => panic!("unreachable"),
+            };
+
+            debug!("Reading data for baseline: {baseline}/{flavour} (file: {csv_file:?}");
+
+            let mut file_runs = BTreeMap::<u32, BTreeMap<ContainerdEvent, f64>>::new();
+            for record in Self::read_data_file::<Record>(args, csv_file) {
+                if let Ok(event) = record.event.parse() {
+                    file_runs
+                        .entry(record.run)
+                        .or_default()
+                        .insert(event, record.time_ms as f64);
+                }
+            }
+
+            let baseline_runs = runs.entry(baseline).or_default();
+            for run in file_runs.values() {
+                let mut events = BTreeMap::<ContainerdEvent, f64>::new();
+                let mut orchestration_time = *run.get(&ContainerdEvent::StartUp).unwrap_or(&0.0);
+                for event in ContainerdEvent::iter_variants() {
+                    if *event == ContainerdEvent::StartUp {
+                        continue;
+                    }
+
+                    // As in the bar plot, we carve events out of their parent
+                    let time = *run.get(event).unwrap_or(&0.0);
+                    match event.parent_event() {
+                        Some(parent) => {
+                            let time = time.min(events[&parent]);
+                            *events.get_mut(&parent).unwrap() -= time;
+                            events.insert(*event, time);
+                        }
+                        None => {
+                            orchestration_time -= time;
+                            events.insert(*event, time);
+                        }
+                    }
+                }
+                events.insert(ContainerdEvent::StartUp, orchestration_time);
+                baseline_runs.push(events);
+            }
+        }
+
+        // Work-out the fraction of the end-to-end variance that each event
+        // contributes. Events that co-vary negatively with the end-to-end time
+        // would have a negative contribution, so we clamp them to zero and
+        // re-normalise the rest
+        let mut cold_data = BTreeMap::<AvailableBaselines, BTreeMap<ContainerdEvent, f64>>::new();
+        let mut warm_data = BTreeMap::<AvailableBaselines, BTreeMap<ContainerdEvent, f64>>::new();
+        for (runs, data) in [(&cold_runs, &mut cold_data), (&warm_runs, &mut warm_data)] {
+            for baseline in AvailableBaselines::iter_variants() {
+                if !Self::is_baseline_selected(args, baseline) {
+                    continue;
+                }
+
+                let mut inner_map = BTreeMap::<ContainerdEvent, f64>::new();
+                for event in ContainerdEvent::iter_variants() {
+                    inner_map.insert(*event, 0.0);
+                }
+
+                if let Some(baseline_runs) = runs.get(baseline) {
+                    let totals: Vec<f64> = baseline_runs
+                        .iter()
+                        .map(|events| events.values().sum())
+                        .collect();
+                    let total_mean = Self::mean(&totals);
+
+                    for event in ContainerdEvent::iter_variants() {
+                        let event_samples: Vec<f64> =
+                            baseline_runs.iter().map(|events| events[event]).collect();
+                        let event_mean = Self::mean(&event_samples);
+                        let covariance = event_samples
+                            .iter()
+                            .zip(totals.iter())
+                            .map(|(x, t)| (x - event_mean) * (t - total_mean))
+                            .sum::<f64>();
+                        inner_map.insert(*event, covariance.max(0.0));
+                    }
+
+                    let sum: f64 = inner_map.values().sum();
+                    if sum > 0.0 {
+                        for fraction in inner_map.values_mut() {
+                            *fraction /= sum;
+                        }
+                    }
+                }
+
+                data.insert(baseline.clone(), inner_map);
+            }
+        }
+
+        for (flavour, data) in [("cold", &cold_data), ("warm", &warm_data)] {
+            for (baseline, fractions) in data.iter() {
+                for (event, fraction) in fractions.iter() {
+                    debug!("{baseline}/{flavour}/{event}: {fraction} of variance");
+                }
+            }
+        }
+
+        // ---------- Plot Data ---------- //
+
+        let plot_path = Self::get_plot_path(exp, "_variance", &args.format);
+        match args.format {
+            PlotFormat::Png => Self::draw_start_up_variance(
+                Self::draw_title(
+                    BitMapBackend::new(&plot_path, (args.width, args.height)).into_drawing_area(),
+                    args,
+                    exp,
+                    "latency variance",
+                    true,
+                ),
+                &plot_path,
+                &cold_data,
+                &warm_data,
+            ),
+            PlotFormat::Svg => Self::draw_start_up_variance(
+                Self::draw_title(
+                    SVGBackend::new(&plot_path, (args.width, args.height)).into_drawing_area(),
+                    args,
+                    exp,
+                    "latency variance",
+                    true,
+                ),
+                &plot_path,
+                &cold_data,
+                &warm_data,
+            ),
+        }
+    }
+
+    fn draw_start_up_variance<DB: DrawingBackend>(
+        root: DrawingArea<DB, Shift>,
+        plot_path: &Path,
+        cold_data: &BTreeMap<AvailableBaselines, BTreeMap<ContainerdEvent, f64>>,
+        warm_data: &BTreeMap<AvailableBaselines, BTreeMap<ContainerdEvent, f64>>,
+    ) {
+        root.fill(&WHITE).unwrap();
+
+        let legend =
+            Self::start_up_legend(&[cold_data, warm_data], ContainerdEvent::iter_variants());
+
+        let x_max = cold_data.len() as f64;
+        let y_max = 100.0;
+        let mut chart = ChartBuilder::on(&root)
+            .x_label_area_size(40)
+            .y_label_area_size(40)
+            .margin(10)
+            .margin_top(Self::legend_margin_px(&root, &legend, 0))
+            .build_cartesian_2d(0.0..x_max, 0f64..y_max)
+            .unwrap();
+
+        chart
+            .configure_mesh()
+            .y_label_style(("sans-serif", 20).into_font())
+            .y_labels(5)
+            .disable_x_mesh()
+            .disable_x_axis()
+            .y_label_formatter(&|y| format!("{:.0}", y))
+            .draw()
+            .unwrap();
+
+        // Manually draw the y-axis label with a custom font and size
+        root.draw(&Text::new(
+            "Start-Up Variance [%]",
+            Self::scale_pos_px(&root, (3, 280)),
+            ("sans-serif", 20)
+                .into_font()
+                .transform(FontTransform::Rotate270)
+                .color(&BLACK),
+        ))
+        .unwrap();
+
+        // Draw one stacked bar for cold starts, and one for warm starts, for
+        // each baseline
+        let bar_width = 0.5;
+        for (data_idx, data) in (0..).zip([cold_data, warm_data]) {
+            for (x, (_, fractions)) in (0..).zip(data.iter()) {
+                let x_orig: f64 = x as f64 + 0.5 * data_idx as f64;
+                let mut prev_y = 0.0;
+                for event in ContainerdEvent::iter_variants() {
+                    let this_color = if data_idx == 0 {
+                        event.color().into()
+                    } else {
+                        event.color().mix(0.6)
+                    };
+                    let this_y = fractions[event] * y_max;
+
+                    let mut bar = Rectangle::new(
+                        [(x_orig, prev_y), (x_orig + bar_width, prev_y + this_y)],
+                        this_color.filled(),
+                    );
+                    if data_idx == 0 {
+                        bar.set_margin(0, 0, 2, 0);
+                    } else {
+                        bar.set_margin(0, 0, 0, 2);
+                    }
+                    chart.draw_series(std::iter::once(bar)).unwrap();
+                    prev_y += this_y;
+                }
+
+                // Add black frame around each (non-empty) bar
+                if prev_y > 0.0 {
+                    let mut frame = Rectangle::new(
+                        [(x_orig, 0.0), (x_orig + bar_width, prev_y)],
+                        BLACK.stroke_width(1),
+                    );
+                    if data_idx == 0 {
+                        frame.set_margin(0, 0, 2, 0);
+                    } else {
+                        frame.set_margin(0, 0, 0, 2);
+                    }
+                    chart.draw_series(std::iter::once(frame)).unwrap();
+                }
+            }
+        }
+
+        // Add solid frames around grid
+        chart
+            .plotting_area()
+            .draw(&PathElement::new(vec![(0.0, y_max), (x_max, y_max)], BLACK))
+            .unwrap();
+        chart
+            .plotting_area()
+            .draw(&PathElement::new(vec![(x_max, 0.0), (x_max, y_max)], BLACK))
+            .unwrap();
+
+        Self::draw_start_up_labels(
+            &root,
+            chart.plotting_area().get_pixel_range().0,
+            &cold_data.keys().collect::<Vec<_>>(),
+            &legend,
+            false,
+        );
+
+        println!(
+            "{}(plot): generated plot at: {}",
+            Env::SYS_NAME,
+            plot_path.display()
+        );
+        root.present().unwrap();
+    }
+
+    /// Plot the cumulative distribution function of the end-to-end start-up
+    /// latency, with one line per baseline and flavour
+    fn plot_start_up_cdf(exp: &AvailableExperiments, args: &PlotArgs, data_files: &[PathBuf]) {
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            event: String,
+            time_ms: u64,
+        }
+
+        // ---------- Collect Data ---------- //
+
+        // Collect all the end-to-end samples for each baseline and flavour,
+        // including the ones in sweep sub-directories
+        let mut samples = BTreeMap::<(AvailableBaselines, String), Vec<f64>>::new();
+        let sweep_files =
+            Self::get_sweep_data_files(&Self::get_data_path(&Env::results_root(), exp), args);
+        for csv_file in data_files.iter().chain(sweep_files.values().flatten()) {
+            let file_name_no_ext = csv_file
+                .file_stem()
+                .and_then(|f| f.to_str())
+                .unwrap_or_default();
+            let baseline: AvailableBaselines = file_name_no_ext.split('_').collect::<Vec<_>>()[0]
+                .parse()
+                .unwrap();
+            let flavour = file_name_no_ext.split('_').collect::<Vec<_>>()[1].to_string();
+
+            debug!("Reading data for baseline: {baseline}/{flavour} (file: {csv_file:?}");
+
+            let baseline_samples = samples.entry((baseline, flavour)).or_default();
+            for record in Self::read_data_file::<Record>(args, csv_file) {
+                if record.event.parse() == Ok(ContainerdEvent::StartUp) {
+                    baseline_samples.push(record.time_ms as f64 / 1000.0);
+                }
+            }
+        }
+
+        // Sort the samples to build the CDF
+        let mut x_max: f64 = 0.0;
+        for baseline_samples in samples.values_mut() {
+            baseline_samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            if let Some(max) = baseline_samples.last() {
+                x_max = x_max.max(*max);
+            }
+        }
+
+        // ---------- Plot Data ---------- //
+
+        let plot_path = Self::get_plot_path(exp, "_cdf", &args.format);
+        match args.format {
+            PlotFormat::Png => Self::draw_start_up_cdf(
+                Self::draw_title(
+                    BitMapBackend::new(&plot_path, (args.width, args.height)).into_drawing_area(),
+                    args,
+                    exp,
+                    "latency CDF",
+                    true,
+                ),
+                &plot_path,
+                &samples,
+                x_max,
+            ),
+            PlotFormat::Svg => Self::draw_start_up_cdf(
+                Self::draw_title(
+                    SVGBackend::new(&plot_path, (args.width, args.height)).into_drawing_area(),
+                    args,
+                    exp,
+                    "latency CDF",
+                    true,
+                ),
+                &plot_path,
+                &samples,
+                x_max,
+            ),
+        }
+    }
+
+    fn draw_start_up_cdf<DB: DrawingBackend>(
+        root: DrawingArea<DB, Shift>,
+        plot_path: &Path,
+        samples: &BTreeMap<(AvailableBaselines, String), Vec<f64>>,
+        x_max: f64,
+    ) {
+        root.fill(&WHITE).unwrap();
+
+        let x_max = x_max.ceil().max(1.0);
+        let legend = Self::baselines_legend(
+            samples
+                .iter()
+                .filter(|(_, baseline_samples)| !baseline_samples.is_empty())
+                .map(|((baseline, _), _)| baseline),
+        );
+        let mut chart = ChartBuilder::on(&root)
+            .x_label_area_size(40)
+            .y_label_area_size(55)
+            .margin(10)
+            .margin_top(Self::legend_margin_px(&root, &legend, 0))
+            .build_cartesian_2d(0.0..x_max, 0f64..1.0)
+            .unwrap();
+
+        chart
+            .configure_mesh()
+            .x_label_style(("sans-serif", 20).into_font())
+            .y_label_style(("sans-serif", 20).into_font())
+            .x_labels(5)
+            .y_labels(5)
+            .disable_x_mesh()
+            .x_label_formatter(&|x| format!("{:.0}", x))
+            .y_label_formatter(&|y| format!("{:.1}", y))
+            .draw()
+            .unwrap();
+
+        // Manually draw the axis labels with a custom font and size
+        root.draw(&Text::new(
+            "CDF",
+            Self::scale_pos_px(&root, (3, 200)),
+            ("sans-serif", 20)
+                .into_font()
+                .transform(FontTransform::Rotate270)
+                .color(&BLACK),
+        ))
+        .unwrap();
+        root.draw(&Text::new(
+            "Start-Up Latency [s]",
+            Self::scale_pos_px(&root, (200, 375)),
+            ("sans-serif", 20).into_font().color(&BLACK),
+        ))
+        .unwrap();
+
+        // Draw one step line per baseline. Cold starts use the baseline's
+        // color, and warm starts a lighter shade of it
+        for ((baseline, flavour), baseline_samples) in samples.iter() {
+            // Without any samples (e.g. if --phase filters out every run),
+            // there is no step to draw
+            if baseline_samples.is_empty() {
+                continue;
+            }
 
-        let mut csv_files = Vec::new();
-        for entry in fs::read_dir(data_path).unwrap() {
-            let entry = entry.unwrap();
-            if entry.path().extension().and_then(|e| e.to_str()) == Some("csv") {
-                csv_files.push(entry.path());
+            let num_samples = baseline_samples.len() as f64;
+            let mut points = vec![(0.0, 0.0)];
+            for (idx, sample) in (0..).zip(baseline_samples.iter()) {
+                points.push((*sample, idx as f64 / num_samples));
+                points.push((*sample, (idx + 1) as f64 / num_samples));
             }
+            points.push((x_max, 1.0));
+
+            let color = if flavour == "cold" {
+                baseline.get_color().into()
+            } else {
+                baseline.get_color().mix(0.6)
+            };
+            chart
+                .draw_series(LineSeries::new(points, color.stroke_width(2)))
+                .unwrap();
         }
 
-        csv_files
+        // Add solid frames around grid
+        chart
+            .plotting_area()
+            .draw(&PathElement::new(vec![(0.0, 1.0), (x_max, 1.0)], BLACK))
+            .unwrap();
+        chart
+            .plotting_area()
+            .draw(&PathElement::new(vec![(x_max, 0.0), (x_max, 1.0)], BLACK))
+            .unwrap();
+
+        Self::draw_legend(&root, &legend);
+
+        println!(
+            "{}(plot): generated plot at: {}",
+            Env::SYS_NAME,
+            plot_path.display()
+        );
+        root.present().unwrap();
     }
 
-    fn plot_start_up_latency(exp: &AvailableExperiments, data_files: &Vec<PathBuf>) {
+    fn plot_registry_rtt(exp: &AvailableExperiments, args: &PlotArgs, data_files: &Vec<PathBuf>) {
         #[derive(Debug, Deserialize)]
         #[serde(rename_all = "PascalCase")]
         struct Record {
-            #[allow(dead_code)]
             run: u32,
             event: String,
             time_ms: u64,
@@ -43,243 +2576,176 @@ impl Plot {
         // ---------- Collect Data ---------- //
 
         // This map has one key per baseline, and each baseline holds a map
-        // of each event and the average time spent in each event.
-        // Note: we stack averages together, which may not be the most
-        // statistically-wise thing.
-        let mut cold_data = BTreeMap::<AvailableBaselines, BTreeMap<&str, f64>>::new();
-        for workflow in AvailableBaselines::iter_variants() {
-            let mut inner_map = BTreeMap::<&str, f64>::new();
-            for event in Containerd::CONTAINERD_INFO_EVENTS {
-                inner_map.insert(event, 0.0);
-            }
-            cold_data.insert(workflow.clone(), inner_map);
-        }
-        let mut warm_data = BTreeMap::<AvailableBaselines, BTreeMap<&str, f64>>::new();
-        for workflow in AvailableBaselines::iter_variants() {
-            let mut inner_map = BTreeMap::<&str, f64>::new();
-            for event in Containerd::CONTAINERD_INFO_EVENTS {
-                inner_map.insert(event, 0.0);
-            }
-            warm_data.insert(workflow.clone(), inner_map);
-        }
-
-        let mut y_max: f64 = 25.0e3;
+        // from the injected delay to the image pull latency of each run. We
+        // define the image pull latency as the time spent pulling the image
+        // in the host plus the time spent pulling the image in the guest.
+        // Data files in different sub-directories may hold runs for the same
+        // baseline and delay, so we pool their runs together
+        Self::warn_on_duplicate_data_files(data_files);
+        let mut pull_times = BTreeMap::<AvailableBaselines, BTreeMap<u32, Vec<f64>>>::new();
         for csv_file in data_files {
-            let file_name = csv_file
-                .file_name()
+            let file_name_no_ext = csv_file
+                .file_stem()
                 .and_then(|f| f.to_str())
                 .unwrap_or_default();
-            let file_name_len = file_name.len();
-            let file_name_no_ext = &file_name[0..file_name_len - 4];
             let baseline: AvailableBaselines = file_name_no_ext.split('_').collect::<Vec<_>>()[0]
                 .parse()
                 .unwrap();
-            let flavour: String = file_name_no_ext.split('_').collect::<Vec<_>>()[1]
+            let delay_ms: u32 = file_name_no_ext.split('_').collect::<Vec<_>>()[1]
                 .parse()
                 .unwrap();
 
-            // Based on the flavour, we pick one of the data dictionaries
-            let data = match flavour.as_str() {
-                "cold" => &mut cold_data,
-                "warm" => &mut warm_data,
-                _ => panic!("unreachable"),
-            };
-
-            debug!("Reading data for baseline: {baseline}/{flavour} (file: {csv_file:?}");
-
-            // Open the CSV and deserialize records
-            let mut reader = ReaderBuilder::new()
-                .has_headers(true)
-                .from_path(csv_file)
-                .unwrap();
-            let mut count = 0;
-
-            // Aggregate all results
-            for result in reader.deserialize() {
-                let record: Record = result.unwrap();
-                let this_event = data
-                    .get_mut(&baseline)
-                    .unwrap()
-                    .get_mut(record.event.as_str())
-                    .unwrap();
-                *this_event += record.time_ms as f64;
+            debug!("Reading data for baseline: {baseline}/{delay_ms}ms (file: {csv_file:?}");
 
-                count += 1;
+            // Aggregate the pull latency for each run
+            let mut file_pull_times = BTreeMap::<u32, f64>::new();
+            for record in Self::read_data_file::<Record>(args, csv_file) {
+                if matches!(
+                    record.event.parse(),
+                    Ok(ContainerdEvent::PullImage | ContainerdEvent::StartContainerUserContainer)
+                ) {
+                    *file_pull_times.entry(record.run).or_insert(0.0) += record.time_ms as f64;
+                }
             }
 
-            // Calculate the average
-            let num_reps = count / Containerd::CONTAINERD_INFO_EVENTS.len();
-            let mut orchestration_time = 0.0;
-            for (event, agg) in data.get_mut(&baseline).unwrap() {
-                *agg /= num_reps as f64;
+            pull_times
+                .entry(baseline)
+                .or_default()
+                .entry(delay_ms)
+                .or_default()
+                .extend(file_pull_times.into_values());
+        }
 
-                if *event != "StartUp" {
-                    orchestration_time += *agg;
-                }
+        // This map has one key per baseline, and each baseline holds a map
+        // from the injected delay to the average image pull latency
+        let mut data = BTreeMap::<AvailableBaselines, BTreeMap<u32, f64>>::new();
+        let mut x_max: u32 = 0;
+        let mut y_max: f64 = 0.0;
+        for (baseline, baseline_pull_times) in pull_times {
+            for (delay_ms, times) in baseline_pull_times {
+                let avg_pull_time = if times.is_empty() {
+                    0.0
+                } else {
+                    times.iter().sum::<f64>() / times.len() as f64
+                };
 
-                // Keep track of the highest average
-                if *agg > y_max {
-                    y_max = *agg;
-                }
+                y_max = y_max.max(avg_pull_time);
+                x_max = x_max.max(delay_ms);
+
+                data.entry(baseline.clone())
+                    .or_default()
+                    .insert(delay_ms, avg_pull_time);
             }
+        }
 
-            // Add an additional event corresponding to "Orchestration" which
-            // we define as StartUp - sum(AllOtherEvents)
-            orchestration_time =
-                data.get(&baseline).unwrap().get("StartUp").unwrap() - orchestration_time;
-            data.get_mut(&baseline)
-                .unwrap()
-                .insert("Orchestration", orchestration_time);
-        } // End processing one CSV file
+        for (baseline, times) in data.iter() {
+            for (delay_ms, avg) in times.iter() {
+                debug!("{baseline}/{delay_ms}ms: {avg} ms");
+            }
+        }
 
         // ---------- Plot Data ---------- //
 
-        for flavour in ["cold", "warm"] {
-            let data = match flavour {
-                "cold" => cold_data.clone(),
-                "warm" => warm_data.clone(),
-                _ => panic!("unreachable"),
-            };
-
-            for (baseline, times) in data.iter() {
-                for (event, avg) in times.iter() {
-                    debug!("{baseline}/{flavour}/{event}: {avg} ms");
-                }
-            }
+        let plot_path = Self::get_plot_path(exp, "", &args.format);
+        match args.format {
+            PlotFormat::Png => Self::draw_registry_rtt(
+                Self::draw_title(
+                    BitMapBackend::new(&plot_path, (args.width, args.height)).into_drawing_area(),
+                    args,
+                    exp,
+                    "image pull latency",
+                    true,
+                ),
+                &plot_path,
+                &data,
+                x_max,
+                y_max,
+            ),
+            PlotFormat::Svg => Self::draw_registry_rtt(
+                Self::draw_title(
+                    SVGBackend::new(&plot_path, (args.width, args.height)).into_drawing_area(),
+                    args,
+                    exp,
+                    "image pull latency",
+                    true,
+                ),
+                &plot_path,
+                &data,
+                x_max,
+                y_max,
+            ),
         }
+    }
 
-        let mut plot_path = Env::results_root();
-        plot_path.push(format!("{exp}"));
-        plot_path.push("plots");
-        fs::create_dir_all(plot_path.clone()).unwrap();
-        plot_path.push(format!("{}.svg", exp.to_string().replace("-", "_")));
-
-        let chart_height_px = 600;
-        let chart_width_px = 400;
-        let root =
-            SVGBackend::new(&plot_path, (chart_height_px, chart_width_px)).into_drawing_area();
+    fn draw_registry_rtt<DB: DrawingBackend>(
+        root: DrawingArea<DB, Shift>,
+        plot_path: &Path,
+        data: &BTreeMap<AvailableBaselines, BTreeMap<u32, f64>>,
+        x_max: u32,
+        y_max: f64,
+    ) {
         root.fill(&WHITE).unwrap();
 
-        let x_max = AvailableBaselines::iter_variants().len() as f64;
+        let x_max = x_max.max(1) as f64;
+        let y_max = (y_max / 1000.0).ceil().max(1.0);
+        let legend = Self::baselines_legend(data.keys());
         let mut chart = ChartBuilder::on(&root)
             .x_label_area_size(40)
             .y_label_area_size(40)
             .margin(10)
-            .margin_top(40)
-            .build_cartesian_2d(0.0..x_max, 0f64..(y_max / 1000.0))
+            .margin_top(Self::legend_margin_px(&root, &legend, 0))
+            .build_cartesian_2d(0.0..x_max, 0f64..y_max)
             .unwrap();
 
         chart
             .configure_mesh()
+            .x_label_style(("sans-serif", 20).into_font())
             .y_label_style(("sans-serif", 20).into_font())
+            .x_labels(5)
             .y_labels(10)
             .y_max_light_lines(5)
             .disable_x_mesh()
-            .disable_x_axis()
+            .x_label_formatter(&|x| format!("{:.0}", x))
             .y_label_formatter(&|y| format!("{:.0}", y))
             .draw()
             .unwrap();
 
-        // Manually draw the y-axis label with a custom font and size
+        // Manually draw the axis labels with a custom font and size
         root.draw(&Text::new(
-            "Start-Up Latency [s]",
-            (3, 280),
+            "Image Pull Latency [s]",
+            Self::scale_pos_px(&root, (3, 280)),
             ("sans-serif", 20)
                 .into_font()
                 .transform(FontTransform::Rotate270)
                 .color(&BLACK),
         ))
         .unwrap();
+        root.draw(&Text::new(
+            "Injected Registry RTT [ms]",
+            Self::scale_pos_px(&root, (200, 375)),
+            ("sans-serif", 20).into_font().color(&BLACK),
+        ))
+        .unwrap();
 
-        let bar_width = 0.5;
-        for (data_idx, data) in (0..).zip([cold_data.clone(), warm_data.clone()]) {
-            // Draw bars: we draw one series for each event, and we stack them
-            // together
-            let mut prev_y_map: BTreeMap<&AvailableBaselines, f64> = BTreeMap::new();
-            for baseline in AvailableBaselines::iter_variants() {
-                prev_y_map.insert(baseline, 0.0);
-            }
-
-            for event in Containerd::CONTAINERD_INFO_EVENTS {
-                chart
-                    .draw_series((0..).zip(data.iter()).map(|(x, (baseline, event_vec))| {
-                        let this_color = if data_idx == 0 {
-                            Containerd::get_color_for_event(event).into()
-                        } else {
-                            Containerd::get_color_for_event(event).mix(0.6)
-                        };
-                        let bar_style = ShapeStyle {
-                            color: this_color,
-                            filled: true,
-                            stroke_width: 2,
-                        };
-
-                        // Handle the StartUp case separately
-                        let mut this_y = *event_vec.get(event).unwrap();
-                        if event == "StartUp" {
-                            this_y = *event_vec.get("Orchestration").unwrap();
-                        }
-                        let prev_y = prev_y_map.get_mut(baseline).unwrap();
-                        this_y /= 1000.0;
-
-                        let x_orig: f64 = x as f64 + 0.5 * data_idx as f64;
-
-                        let mut bar = Rectangle::new(
-                            [(x_orig, *prev_y), (x_orig + bar_width, *prev_y + this_y)],
-                            bar_style,
-                        );
-                        *prev_y += this_y;
-
-                        // Set the margins so that bars for the same baseline
-                        // touch
-                        if data_idx == 0 {
-                            bar.set_margin(0, 0, 2, 0);
-                        } else {
-                            bar.set_margin(0, 0, 0, 2);
-                        }
-
-                        bar
-                    }))
-                    .unwrap();
-            }
+        // Draw one line (with markers) per baseline
+        for (baseline, times) in data.iter() {
+            let points: Vec<(f64, f64)> = times
+                .iter()
+                .map(|(delay_ms, avg)| (*delay_ms as f64, *avg / 1000.0))
+                .collect();
 
-            // Add black frame around each bar
             chart
-                .draw_series((0..).zip(data.iter()).map(|(x, (baseline, _))| {
-                    // Benefit from the fact that prev_y stores the maximum y
-                    // value after we plot the stacked bar chart
-                    let this_y = *prev_y_map.get_mut(baseline).unwrap();
-
-                    let x_orig: f64 = x as f64 + 0.5 * data_idx as f64;
-                    let margin_px = 2;
-                    let x_axis_range = 0.0..x_max;
-                    let margin_units = margin_px as f64 * (x_axis_range.end - x_axis_range.start)
-                        / chart_width_px as f64;
-
-                    if data_idx == 0 {
-                        PathElement::new(
-                            vec![
-                                (x_orig + margin_units, this_y),
-                                (x_orig + bar_width, this_y),
-                                (x_orig + bar_width, 0.0),
-                                (x_orig + margin_units, 0.0),
-                                (x_orig + margin_units, this_y),
-                            ],
-                            BLACK,
-                        )
-                    } else {
-                        PathElement::new(
-                            vec![
-                                (x_orig, this_y),
-                                (x_orig - margin_units + bar_width, this_y),
-                                (x_orig - margin_units + bar_width, 0.0),
-                                (x_orig, 0.0),
-                                (x_orig, this_y),
-                            ],
-                            BLACK,
-                        )
-                    }
-                }))
+                .draw_series(LineSeries::new(
+                    points.clone(),
+                    baseline.get_color().stroke_width(2),
+                ))
+                .unwrap();
+            chart
+                .draw_series(
+                    points
+                        .into_iter()
+                        .map(|(x, y)| Circle::new((x, y), 4, baseline.get_color().filled())),
+                )
                 .unwrap();
         }
 
@@ -295,102 +2761,227 @@ impl Plot {
                 BLACK,
             ))
             .unwrap();
-        chart
-            .plotting_area()
-            .draw(&PathElement::new(
-                vec![(0.0, 0 as f64), (x_max, 0 as f64)],
-                BLACK,
-            ))
-            .unwrap();
 
-        // Manually draw the x-axis labels with a custom font and size
-        fn xaxis_pos_for_baseline(baseline: &AvailableBaselines) -> i32 {
-            match baseline {
-                AvailableBaselines::Runc => 80,
-                AvailableBaselines::Kata => 180,
-                AvailableBaselines::Snp => 260,
-                AvailableBaselines::SnpSc2 => 340,
-                AvailableBaselines::Tdx => 445,
-                AvailableBaselines::TdxSc2 => 520,
-            }
+        Self::draw_legend(&root, &legend);
+
+        println!(
+            "{}(plot): generated plot at: {}",
+            Env::SYS_NAME,
+            plot_path.display()
+        );
+        root.present().unwrap();
+    }
+
+    fn plot_scale_out(exp: &AvailableExperiments, args: &PlotArgs, data_files: &Vec<PathBuf>) {
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            #[allow(dead_code)]
+            run: u32,
+            scale_idx: Option<String>,
+            time_ms: u64,
         }
 
-        for (_, baseline) in (0..).zip(AvailableBaselines::iter_variants()) {
-            root.draw(&Text::new(
-                format!("{baseline}"),
-                (xaxis_pos_for_baseline(baseline), 360),
-                ("sans-serif", 20).into_font().color(&BLACK),
-            ))
-            .unwrap();
+        // ---------- Collect Data ---------- //
+
+        // Each data file holds the time to scale out to each number of
+        // replicas (the scale index) in each run. Older data files held a
+        // single scale index, that we take from the file name. Data files in
+        // different sub-directories may hold runs for the same baseline, so
+        // we pool their runs together
+        Self::warn_on_duplicate_data_files(data_files);
+        let mut times = BTreeMap::<AvailableBaselines, BTreeMap<u32, Vec<f64>>>::new();
+        for csv_file in data_files {
+            let file_name_no_ext = csv_file
+                .file_stem()
+                .and_then(|f| f.to_str())
+                .unwrap_or_default();
+            let baseline: AvailableBaselines = file_name_no_ext.split('_').collect::<Vec<_>>()[0]
+                .parse()
+                .unwrap();
+            let file_scale_idx: u32 = file_name_no_ext.split('_').collect::<Vec<_>>()[1]
+                .parse()
+                .unwrap();
+
+            debug!("Reading data for baseline: {baseline}/{file_scale_idx} (file: {csv_file:?}");
+
+            for record in Self::read_data_file::<Record>(args, csv_file) {
+                let scale_idx = match &record.scale_idx {
+                    Some(scale_idx) => scale_idx.parse().unwrap(),
+                    None => file_scale_idx,
+                };
+                times
+                    .entry(baseline.clone())
+                    .or_default()
+                    .entry(scale_idx)
+                    .or_default()
+                    .push(record.time_ms as f64);
+            }
         }
 
-        // Manually draw the legend outside the grid, above the chart
-        let legend_labels = vec![
-            "control-plane",
-            "create-vm",
-            "pull-image-host",
-            "pull-image-guest",
-        ];
+        // This map has one key per baseline, and each baseline holds a map
+        // from the scale index to the (average, min, max) scale-out latency
+        let mut data = BTreeMap::<AvailableBaselines, BTreeMap<u32, (f64, f64, f64)>>::new();
+        let mut x_max: u32 = 0;
+        let mut y_max: f64 = 0.0;
+        for (baseline, baseline_times) in &times {
+            for (scale_idx, times) in baseline_times {
+                let avg = times.iter().sum::<f64>() / times.len() as f64;
+                let min = times.iter().cloned().fold(f64::INFINITY, f64::min);
+                let max = times.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
 
-        fn legend_pos_for_label(label: &str) -> (i32, i32) {
-            let legend_x_start = 20;
-            let legend_y_pos = 6;
+                y_max = y_max.max(max);
+                x_max = x_max.max(*scale_idx);
 
-            match label {
-                "control-plane" => (legend_x_start, legend_y_pos),
-                "create-vm" => (legend_x_start + 140, legend_y_pos),
-                "pull-image-host" => (legend_x_start + 255, legend_y_pos),
-                "pull-image-guest" => (legend_x_start + 410, legend_y_pos),
-                _ => panic!("{}(plot): unrecognised label: {label}", Env::SYS_NAME),
+                data.entry(baseline.clone())
+                    .or_default()
+                    .insert(*scale_idx, (avg, min, max));
             }
         }
 
-        fn legend_color_for_label(label: &str) -> RGBColor {
-            match label {
-                "control-plane" => Containerd::get_color_for_event("StartUp"),
-                "create-vm" => Containerd::get_color_for_event("RunPodSandbox"),
-                "pull-image-host" => Containerd::get_color_for_event("PullImage"),
-                "pull-image-guest" => {
-                    Containerd::get_color_for_event("StartContainerUserContainer")
-                }
-                _ => panic!("{}(plot): unrecognised label: {label}", Env::SYS_NAME),
+        for (baseline, times) in data.iter() {
+            for (scale_idx, (avg, min, max)) in times.iter() {
+                debug!("{baseline}/{scale_idx}: {avg} ms (min: {min}, max: {max})");
             }
         }
 
-        for label in legend_labels {
-            // Calculate position for each legend item
-            let (x_pos, y_pos) = legend_pos_for_label(label);
+        // ---------- Plot Data ---------- //
 
-            // Draw the color box (Rectangle)
-            root.draw(&Rectangle::new(
-                [(x_pos, y_pos), (x_pos + 20, y_pos + 20)],
-                legend_color_for_label(label).filled(),
-            ))
+        let plot_path = Self::get_plot_path(exp, "", &args.format);
+        match args.format {
+            PlotFormat::Png => Self::draw_scale_out(
+                Self::draw_title(
+                    BitMapBackend::new(&plot_path, (args.width, args.height)).into_drawing_area(),
+                    args,
+                    exp,
+                    "latency",
+                    true,
+                ),
+                &plot_path,
+                &data,
+                x_max,
+                y_max,
+            ),
+            PlotFormat::Svg => Self::draw_scale_out(
+                Self::draw_title(
+                    SVGBackend::new(&plot_path, (args.width, args.height)).into_drawing_area(),
+                    args,
+                    exp,
+                    "latency",
+                    true,
+                ),
+                &plot_path,
+                &data,
+                x_max,
+                y_max,
+            ),
+        }
+    }
+
+    fn draw_scale_out<DB: DrawingBackend>(
+        root: DrawingArea<DB, Shift>,
+        plot_path: &Path,
+        data: &BTreeMap<AvailableBaselines, BTreeMap<u32, (f64, f64, f64)>>,
+        x_max: u32,
+        y_max: f64,
+    ) {
+        root.fill(&WHITE).unwrap();
+
+        let x_max = x_max.max(1) as f64;
+        let y_max = (y_max / 1000.0).ceil().max(1.0);
+        let legend = Self::baselines_legend(data.keys());
+        let mut chart = ChartBuilder::on(&root)
+            .x_label_area_size(40)
+            .y_label_area_size(40)
+            .margin(10)
+            .margin_top(Self::legend_margin_px(&root, &legend, 0))
+            .build_cartesian_2d(0.0..x_max, 0f64..y_max)
             .unwrap();
 
-            // Draw the baseline label (Text)
-            root.draw(&Text::new(
-                label,
-                (x_pos + 30, y_pos + 5),
-                ("sans-serif", 20).into_font(),
-            ))
+        chart
+            .configure_mesh()
+            .x_label_style(("sans-serif", 20).into_font())
+            .y_label_style(("sans-serif", 20).into_font())
+            .x_labels(x_max as usize + 1)
+            .y_labels(10)
+            .y_max_light_lines(5)
+            .disable_x_mesh()
+            .x_label_formatter(&|x| format!("{:.0}", x))
+            .y_label_formatter(&|y| format!("{:.0}", y))
+            .draw()
             .unwrap();
-        }
 
-        // Manually draw cold/warm labels for one bar
+        // Manually draw the axis labels with a custom font and size
         root.draw(&Text::new(
-            "cold",
-            (60, 300),
-            ("sans-serif", 14).into_font(),
+            "Scale-Out Latency [s]",
+            Self::scale_pos_px(&root, (3, 280)),
+            ("sans-serif", 20)
+                .into_font()
+                .transform(FontTransform::Rotate270)
+                .color(&BLACK),
         ))
         .unwrap();
         root.draw(&Text::new(
-            "warm",
-            (100, 320),
-            ("sans-serif", 14).into_font(),
+            "Scale Index",
+            Self::scale_pos_px(&root, (250, 375)),
+            ("sans-serif", 20).into_font().color(&BLACK),
         ))
         .unwrap();
 
+        for (baseline, times) in data.iter() {
+            // First draw a thin shaded band between the min and the max
+            let mut band: Vec<(f64, f64)> = times
+                .iter()
+                .map(|(scale_idx, (_, _, max))| (*scale_idx as f64, *max / 1000.0))
+                .collect();
+            band.extend(
+                times
+                    .iter()
+                    .rev()
+                    .map(|(scale_idx, (_, min, _))| (*scale_idx as f64, *min / 1000.0)),
+            );
+            chart
+                .draw_series(std::iter::once(Polygon::new(
+                    band,
+                    baseline.get_color().mix(0.3).filled(),
+                )))
+                .unwrap();
+
+            // Then draw the average (with markers) on top
+            let points: Vec<(f64, f64)> = times
+                .iter()
+                .map(|(scale_idx, (avg, _, _))| (*scale_idx as f64, *avg / 1000.0))
+                .collect();
+            chart
+                .draw_series(LineSeries::new(
+                    points.clone(),
+                    baseline.get_color().stroke_width(2),
+                ))
+                .unwrap();
+            chart
+                .draw_series(
+                    points
+                        .into_iter()
+                        .map(|(x, y)| Circle::new((x, y), 4, baseline.get_color().filled())),
+                )
+                .unwrap();
+        }
+
+        // Add solid frames around grid
+        chart
+            .plotting_area()
+            .draw(&PathElement::new(vec![(0.0, y_max), (x_max, y_max)], BLACK))
+            .unwrap();
+        chart
+            .plotting_area()
+            .draw(&PathElement::new(
+                vec![(x_max, 0 as f64), (x_max, y_max)],
+                BLACK,
+            ))
+            .unwrap();
+
+        Self::draw_legend(&root, &legend);
+
         println!(
             "{}(plot): generated plot at: {}",
             Env::SYS_NAME,
@@ -399,16 +2990,52 @@ impl Plot {
         root.present().unwrap();
     }
 
-    pub fn plot(exp: &AvailableExperiments) {
+    pub fn plot(exp: &AvailableExperiments, args: &PlotArgs) {
+        if args.compare_dir.is_some()
+            && (*exp != AvailableExperiments::StartUp || !matches!(args.kind, PlotKind::Bars))
+        {
+            warn!(
+                "{}(plot): --compare-dir only applies to the start-up bar plot, ignoring it",
+                Env::SYS_NAME
+            );
+        }
+        if args.log_scale
+            && (*exp != AvailableExperiments::StartUp || !matches!(args.kind, PlotKind::Bars))
+        {
+            warn!(
+                "{}(plot): --log-scale only applies to the start-up bar plot, ignoring it",
+                Env::SYS_NAME
+            );
+        }
+
         // First, get all the data files for the experiment
-        let data_files = Self::get_all_data_files(exp);
+        let data_files =
+            Self::get_all_data_files(&Self::get_data_path(&Env::results_root(), exp), exp, args);
 
         match exp {
+            AvailableExperiments::RegistryRtt => {
+                Self::plot_registry_rtt(exp, args, &data_files);
+            }
             AvailableExperiments::ScaleOut => {
-                panic!("not implemented :-(");
+                Self::plot_scale_out(exp, args, &data_files);
             }
-            AvailableExperiments::StartUp => {
-                Self::plot_start_up_latency(exp, &data_files);
+            AvailableExperiments::StartUp => match args.kind {
+                PlotKind::Bars => {
+                    Self::plot_start_up_latency(exp, args, &data_files);
+                    Self::plot_containerd_cpu(exp, args, &data_files);
+                }
+                PlotKind::Cdf => {
+                    Self::plot_start_up_cdf(exp, args, &data_files);
+                }
+                PlotKind::Variance => {
+                    Self::plot_start_up_variance(exp, args, &data_files);
+                }
+            },
+            AvailableExperiments::Throughput => {
+                println!(
+                    "{}(plot): plotting is not supported for {exp} yet",
+                    Env::SYS_NAME
+                );
             }
         }
     }